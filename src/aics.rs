@@ -0,0 +1,340 @@
+use std::{collections::VecDeque, rc::Rc};
+
+use crate::{
+    board::{Board, Cell},
+    misc::{box_lines, cells, digits, is_set, units, BoxLine, Visibility},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct Aics {
+    /// Longest chain searched for, in nodes.
+    pub max_length: usize,
+}
+
+/// A candidate in a single cell, or a group of candidates for one digit confined to a box/line intersection, true when
+/// any of its cells holds the digit.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Node {
+    pub cells: Vec<Cell>,
+    pub val: u32,
+}
+
+/// Nodes joined alternately by strong and weak links, starting and ending with a strong link so that one of the ends is
+/// true. A continuous loop also has a weak link from the last node back to the first.
+#[derive(Debug, Clone)]
+pub struct Aic {
+    pub nodes: Vec<Node>,
+    pub continuous: bool,
+    pub placed: Vec<(Cell, u32)>,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+/// The links between nodes, by index into `nodes`.
+struct Graph {
+    nodes: Vec<Node>,
+    strong: Vec<Vec<usize>>,
+    weak: Vec<Vec<usize>>,
+    /// The unsolved cells holding each digit.
+    candidates: Vec<Vec<Cell>>,
+}
+
+impl Default for Aics {
+    fn default() -> Self {
+        Aics { max_length: 12 }
+    }
+}
+
+impl Technique for Aics {
+    fn name(&self) -> &'static str {
+        "AIC"
+    }
+
+    fn difficulty(&self) -> f32 {
+        7.0
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board, self.max_length).iter().map(Aic::deduction).collect()
+    }
+}
+
+/// Searches outward from every node for the shortest chains to each other node, keeping only the shortest chain for
+/// each elimination or placement.
+pub fn from_board(board: &Board, max_length: usize) -> Rc<[Aic]> {
+    let sees = Visibility::new(board);
+    let graph = Graph::new(board, &sees);
+
+    let mut found: Vec<_> = (0..graph.nodes.len()).flat_map(|start| graph.search(board, &sees, start, max_length)).collect();
+    found.sort_by_key(|aic| aic.nodes.len());
+
+    let mut seen = vec![];
+    found.retain_mut(|aic| {
+        aic.placed.retain(|placement| !seen.contains(placement));
+        aic.eliminated.retain(|elimination| !seen.contains(elimination));
+        seen.extend(aic.placed.iter().chain(&aic.eliminated).copied());
+        !aic.placed.is_empty() || !aic.eliminated.is_empty()
+    });
+
+    found.into()
+}
+
+impl Graph {
+    fn new(board: &Board, sees: &Visibility) -> Self {
+        let mut nodes: Vec<_> = cells(board)
+            .iter()
+            .filter(|cell| board[**cell].count_ones() > 1)
+            .flat_map(|cell| digits(board[*cell]).map(|val| Node { cells: vec![*cell], val }))
+            .collect();
+        nodes.extend(groups(board));
+
+        // The cells holding each digit, for every unit
+        let holdings: Vec<Vec<Vec<Cell>>> = units(board)
+            .iter()
+            .map(|unit| {
+                let unit_cells = unit.cells(board.size);
+                (0..=board.size).map(|val| unit_cells.iter().copied().filter(|cell| is_set!(board[*cell], val)).collect()).collect()
+            })
+            .collect();
+        let mut strong = vec![vec![]; nodes.len()];
+        let mut weak = vec![vec![]; nodes.len()];
+
+        for (i, a) in nodes.iter().enumerate() {
+            for (j, b) in nodes.iter().enumerate().skip(i + 1) {
+                let is_weak = if a.val == b.val {
+                    a.cells.iter().all(|x| !b.cells.contains(x) && b.cells.iter().all(|y| sees.get(*x, *y)))
+                } else {
+                    a.cells.len() == 1 && a.cells == b.cells
+                };
+                if !is_weak {
+                    continue;
+                }
+                weak[i].push(j);
+                weak[j].push(i);
+
+                let is_strong = if a.val == b.val {
+                    // The two nodes hold every place for the digit in some unit
+                    holdings
+                        .iter()
+                        .map(|holding| &holding[a.val as usize])
+                        .any(|holding| holding.len() == a.cells.len() + b.cells.len() && holding.iter().all(|cell| a.cells.contains(cell) || b.cells.contains(cell)))
+                } else {
+                    board[a.cells[0]].count_ones() == 2
+                };
+                if is_strong {
+                    strong[i].push(j);
+                    strong[j].push(i);
+                }
+            }
+        }
+
+        let candidates = (0..=board.size)
+            .map(|val| cells(board).iter().copied().filter(|cell| board[*cell].count_ones() > 1 && is_set!(board[*cell], val)).collect())
+            .collect();
+
+        Graph { nodes, strong, weak, candidates }
+    }
+
+    /// Breadth first search from `start` assumed false, alternating strong links to true nodes and weak links to false
+    /// ones, so each true node reached is the far end of a chain from `start`.
+    fn search(&self, board: &Board, sees: &Visibility, start: usize, max_length: usize) -> Vec<Aic> {
+        // States are a node index doubled, plus one when the node is true
+        let mut parent: Vec<Option<usize>> = vec![None; self.nodes.len() * 2];
+        let mut visited = vec![false; self.nodes.len() * 2];
+        let mut queue = VecDeque::from([start * 2]);
+        visited[start * 2] = true;
+        let mut found = vec![];
+
+        while let Some(state) = queue.pop_front() {
+            let path = self.path(&parent, state);
+            let (node, on) = (state / 2, state % 2 == 1);
+
+            if on {
+                found.extend(self.conclude(board, sees, &path));
+                if node == start {
+                    continue;
+                }
+            }
+            if path.len() == max_length {
+                continue;
+            }
+
+            let links = if on { &self.weak[node] } else { &self.strong[node] };
+            for next in links {
+                let next_state = next * 2 + usize::from(!on);
+                let closes = *next == start && !on;
+                if visited[next_state] || (path.contains(next) && !closes) {
+                    continue;
+                }
+                visited[next_state] = true;
+                parent[next_state] = Some(state);
+                queue.push_back(next_state);
+            }
+        }
+
+        found
+    }
+
+    fn path(&self, parent: &[Option<usize>], mut state: usize) -> Vec<usize> {
+        let mut path = vec![state / 2];
+        while let Some(prev) = parent[state] {
+            path.push(prev / 2);
+            state = prev;
+        }
+        path.reverse();
+        path
+    }
+
+    /// What follows from the first or last node of the chain being true.
+    fn conclude(&self, board: &Board, sees: &Visibility, path: &[usize]) -> Option<Aic> {
+        let (first, last) = (&self.nodes[path[0]], &self.nodes[path[path.len() - 1]]);
+        let mut aic = Aic {
+            nodes: path.iter().map(|i| self.nodes[*i].clone()).collect(),
+            continuous: false,
+            placed: vec![],
+            eliminated: vec![],
+        };
+
+        if path[0] == path[path.len() - 1] {
+            // Assuming the node false made it true
+            aic.nodes.pop();
+            if let [cell] = first.cells[..] {
+                aic.placed.push((cell, first.val));
+            }
+        } else if path.len() >= 4 && self.weak[path[0]].contains(&path[path.len() - 1]) {
+            aic.continuous = true;
+            for (a, b) in path.iter().zip(path[1..].iter().chain([&path[0]])) {
+                aic.eliminated.extend(self.link_eliminations(board, sees, &self.nodes[*a], &self.nodes[*b]));
+            }
+        } else if first.val == last.val {
+            aic.eliminated.extend(self.seeing_all(sees, &[first, last], first.val));
+        } else {
+            for (node, other) in [(first, last), (last, first)] {
+                if let [cell] = node.cells[..] {
+                    if other.cells == [cell] {
+                        aic.eliminated.extend(digits(board[cell] & !(1 << node.val | 1 << other.val)).map(|val| (cell, val)));
+                    } else if is_set!(board[cell], other.val) && other.cells.iter().all(|held| *held != cell && sees.get(*held, cell)) {
+                        aic.eliminated.push((cell, other.val));
+                    }
+                }
+            }
+        }
+
+        aic.eliminated.sort_unstable();
+        aic.eliminated.dedup();
+        (!aic.placed.is_empty() || !aic.eliminated.is_empty()).then_some(aic)
+    }
+
+    /// Candidates `val` outside the nodes which see every cell of them.
+    fn seeing_all(&self, sees: &Visibility, nodes: &[&Node], val: u32) -> Vec<(Cell, u32)> {
+        self.candidates[val as usize]
+            .iter()
+            .filter(|cell| nodes.iter().all(|node| node.cells.iter().all(|held| held != *cell && sees.get(*held, **cell))))
+            .map(|cell| (*cell, val))
+            .collect()
+    }
+
+    /// In a continuous loop every link is both weak and strong, so exactly one of its nodes is true.
+    fn link_eliminations(&self, board: &Board, sees: &Visibility, a: &Node, b: &Node) -> Vec<(Cell, u32)> {
+        if a.val == b.val {
+            self.seeing_all(sees, &[a, b], a.val)
+        } else {
+            let cell = a.cells[0];
+            digits(board[cell] & !(1 << a.val | 1 << b.val)).map(|val| (cell, val)).collect()
+        }
+    }
+}
+
+/// The candidates for each digit in each box/line intersection, where there are at least two.
+fn groups(board: &Board) -> Vec<Node> {
+    let mut out = vec![];
+
+    for BoxLine { overlap, .. } in box_lines(board) {
+        let unsolved: Vec<_> = overlap.into_iter().filter(|cell| board[*cell].count_ones() > 1).collect();
+        for val in digits(unsolved.iter().fold(0, |acc, cell| acc | board[*cell])) {
+            let cells: Vec<_> = unsolved.iter().copied().filter(|cell| is_set!(board[*cell], val)).collect();
+            if cells.len() > 1 {
+                out.push(Node { cells, val });
+            }
+        }
+    }
+
+    out
+}
+
+impl Aic {
+    pub fn deduction(&self) -> Deduction {
+        let mut cells = vec![];
+        for cell in self.nodes.iter().flat_map(|node| node.cells.iter()) {
+            if !cells.contains(cell) {
+                cells.push(*cell);
+            }
+        }
+        let digits = self.nodes.iter().fold(0, |acc, node| acc | 1 << node.val);
+
+        let mut deduction = Deduction::new(cells, digits);
+        if self.continuous {
+            deduction.rule = Some("continuous loop");
+        }
+        deduction.placed.clone_from(&self.placed);
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_loop_eliminates_along_weak_links() {
+        // 1 is held by r1c1 and r1c5 in the first row and by r3c1 and r3c6 in the third, making the loop
+        // r1c1 = r1c5 - r3c6 = r3c1 - r1c1
+        let mut board = Board::new(9);
+        for col in 1..9 {
+            if col != 4 {
+                board[Cell { row: 0, col }] &= !(1 << 1);
+            }
+            if col != 5 {
+                board[Cell { row: 2, col }] &= !(1 << 1);
+            }
+        }
+
+        let aics = from_board(&board, 12);
+        assert!(aics.iter().any(|aic| aic.continuous));
+        let mut eliminated: Vec<_> = aics.iter().flat_map(|aic| aic.eliminated.clone()).collect();
+        eliminated.sort_unstable();
+
+        // Cells seeing both r1c5 and r3c6, then both r3c1 and r1c1
+        let box_row = (3..6).map(|col| Cell { row: 1, col });
+        let column = (1..9).filter(|row| *row != 2).map(|row| Cell { row, col: 0 });
+        let box_rest = (1..3).map(|col| Cell { row: 1, col });
+        let mut expected: Vec<_> = box_row.chain(column).chain(box_rest).map(|cell| (cell, 1)).collect();
+        expected.sort_unstable();
+        assert_eq!(eliminated, expected);
+    }
+
+    #[test]
+    fn continuous_loop_eliminates_along_strong_links() {
+        // 1 is held only by c1 and c2 in the first and fourth rows, making the loop r1c1 = r1c2 - r4c2 = r4c1 - r1c1,
+        // whose strong links each also lie in a box
+        let mut board = Board::new(9);
+        for col in 2..9 {
+            board[Cell { row: 0, col }] &= !(1 << 1);
+            board[Cell { row: 3, col }] &= !(1 << 1);
+        }
+
+        let sees = Visibility::new(&board);
+        let graph = Graph::new(&board, &sees);
+        let node = |row, col| graph.nodes.iter().position(|node| node.cells == [Cell { row, col }] && node.val == 1).unwrap();
+        let aic = graph.conclude(&board, &sees, &[node(0, 0), node(0, 1), node(3, 1), node(3, 0)]).unwrap();
+        assert!(aic.continuous);
+
+        // The rest of both columns along the weak links, and the rest of both boxes along the strong links
+        let columns = (0..2).flat_map(|col| (1..9).filter(|row| *row != 3).map(move |row| Cell { row, col }));
+        let boxes = [1, 2, 4, 5].into_iter().map(|row| Cell { row, col: 2 });
+        let mut expected: Vec<_> = columns.chain(boxes).map(|cell| (cell, 1)).collect();
+        expected.sort_unstable();
+        assert_eq!(aic.eliminated, expected);
+    }
+}
@@ -0,0 +1,169 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{almost_locked_sets, cells, digits, is_set, units, Visibility},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct AlignedExclusion<const S: usize>;
+
+// Larger locked sets rarely exclude anything the smaller ones miss
+const MAX_SET_SIZE: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    pub cells: Vec<Cell>,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+macro_rules! impl_aligned {
+    ($size:literal, $name:literal, $difficulty:literal) => {
+        impl Technique for AlignedExclusion<$size> {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board::<$size>(board).iter().map(Alignment::deduction).collect()
+            }
+        }
+    };
+}
+
+impl_aligned!(2, "Aligned Pair Exclusion", 6.2);
+impl_aligned!(3, "Aligned Triple Exclusion", 7.5);
+
+/// Tries every combination of digits in `S` aligned cells. A combination is impossible if two of the cells see each
+/// other and share a digit, or if it would leave a locked set seen by the cells with fewer digits than cells. Digits
+/// which appear in no possible combination are eliminated.
+pub fn from_board<const S: usize>(board: &Board) -> Rc<[Alignment]> {
+    let sees = Visibility::new(board);
+    let sets = locked_sets(board);
+    let mut out = vec![];
+
+    for base in bases(board, &sees, S) {
+        let relevant: Vec<_> = sets
+            .iter()
+            .filter(|(set, _)| set.iter().all(|cell| !base.contains(cell) && base.iter().any(|b| sees.get(*b, *cell))))
+            .collect();
+        // Without a locked set to empty this is no more than a naked subset
+        if relevant.is_empty() {
+            continue;
+        }
+
+        let mut allowed = vec![0; S];
+        let mut combo = vec![];
+        collect_combinations(board, &sees, &base, &relevant, &mut combo, &mut allowed);
+
+        let eliminated: Vec<_> = base
+            .iter()
+            .zip(&allowed)
+            .flat_map(|(cell, allowed)| digits(board[*cell] & !allowed).map(move |val| (*cell, val)))
+            .collect();
+        if !eliminated.is_empty() {
+            out.push(Alignment { cells: base, eliminated });
+        }
+    }
+
+    out.into()
+}
+
+/// Groups of unsolved cells where each cell sees another, with pairs seeing each other directly.
+fn bases(board: &Board, sees: &Visibility, size: usize) -> Vec<Vec<Cell>> {
+    let unsolved: Vec<_> = cells(board).iter().copied().filter(|cell| board[*cell].count_ones() > 1).collect();
+    let mut out = vec![];
+
+    for (i, a) in unsolved.iter().enumerate() {
+        for (j, b) in unsolved.iter().enumerate().skip(i + 1) {
+            if size == 2 {
+                if sees.get(*a, *b) {
+                    out.push(vec![*a, *b]);
+                }
+                continue;
+            }
+            for c in &unsolved[j + 1..] {
+                let links = [sees.get(*a, *b), sees.get(*a, *c), sees.get(*b, *c)];
+                if links.iter().filter(|link| **link).count() >= 2 {
+                    out.push(vec![*a, *b, *c]);
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Almost locked sets in every unit, each listed once.
+fn locked_sets(board: &Board) -> Vec<(Vec<Cell>, u32)> {
+    let mut out: Vec<_> = units(board).iter().flat_map(|unit| almost_locked_sets(board, &unit.cells(board.size), MAX_SET_SIZE)).collect();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+fn collect_combinations(board: &Board, sees: &Visibility, base: &[Cell], sets: &[&(Vec<Cell>, u32)], combo: &mut Vec<u32>, allowed: &mut [u32]) {
+    if combo.len() == base.len() {
+        if sets.iter().all(|set| !empties(board, sees, base, combo, set)) {
+            for (allowed, val) in allowed.iter_mut().zip(combo.iter()) {
+                *allowed |= 1 << val;
+            }
+        }
+        return;
+    }
+
+    let cell = base[combo.len()];
+    for val in digits(board[cell]) {
+        if base.iter().zip(combo.iter()).any(|(other, v)| *v == val && sees.get(*other, cell)) {
+            continue;
+        }
+        combo.push(val);
+        collect_combinations(board, sees, base, sets, combo, allowed);
+        combo.pop();
+    }
+}
+
+/// Whether placing `combo` in `base` leaves the set with fewer digits than cells.
+fn empties(board: &Board, sees: &Visibility, base: &[Cell], combo: &[u32], (set, vals): &(Vec<Cell>, u32)) -> bool {
+    let removed = base.iter().zip(combo).fold(0, |acc, (cell, val)| {
+        if is_set!(vals, val) && set.iter().filter(|c| is_set!(board[**c], val)).all(|c| sees.get(*c, *cell)) {
+            acc | 1 << val
+        } else {
+            acc
+        }
+    });
+    ((vals & !removed).count_ones() as usize) < set.len()
+}
+
+impl Alignment {
+    pub fn deduction(&self) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.clone(), 0);
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_pair_excludes_what_would_empty_a_cell() {
+        // r1c1 {1,2,3} and r1c5 {1,2} both see r1c9 {1,2}, which either 1 2 or 2 1 in the pair would empty
+        let (a, b) = (Cell { row: 0, col: 0 }, Cell { row: 0, col: 4 });
+        let mut board = Board::new(9);
+        board[a] = 1 << 1 | 1 << 2 | 1 << 3;
+        board[b] = 1 << 1 | 1 << 2;
+        board[Cell { row: 0, col: 8 }] = 1 << 1 | 1 << 2;
+
+        let alignments = from_board::<2>(&board);
+        let pair: Vec<_> = alignments.iter().filter(|alignment| alignment.cells == [a, b]).collect();
+        assert_eq!(pair.len(), 1);
+        assert_eq!(pair[0].eliminated, [(a, 1), (a, 2)]);
+    }
+}
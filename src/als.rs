@@ -0,0 +1,329 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    defaults::default_cell,
+    misc::{almost_locked_sets, cells, digits, is_set, units, Visibility},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct AlsXz;
+
+pub struct AlsXyWings;
+
+pub struct DeathBlossoms;
+
+// Bigger sets are rarely needed and make the pair and triple searches much slower
+const MAX_ALS_SIZE: usize = 4;
+
+/// An almost locked set: `n` unsolved cells in one unit holding `n + 1` digits between them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Als {
+    pub cells: Vec<Cell>,
+    pub vals: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlsKind {
+    Xz,
+    DoublyLinkedXz,
+    XyWing,
+    DeathBlossom,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlsChain {
+    pub kind: AlsKind,
+    pub sets: Vec<Als>,
+    /// The stem of a Death Blossom, whose candidates each lead into one of the sets.
+    pub stem: Option<Cell>,
+    /// The restricted common digits linking the sets.
+    pub links: u32,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+impl Technique for AlsXz {
+    fn name(&self) -> &'static str {
+        "ALS-XZ"
+    }
+
+    fn difficulty(&self) -> f32 {
+        7.5
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board_xz(board).iter().map(AlsChain::deduction).collect()
+    }
+}
+
+impl Technique for AlsXyWings {
+    fn name(&self) -> &'static str {
+        "ALS-XY-Wing"
+    }
+
+    fn difficulty(&self) -> f32 {
+        7.8
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board_xy_wing(board).iter().map(AlsChain::deduction).collect()
+    }
+}
+
+impl Technique for DeathBlossoms {
+    fn name(&self) -> &'static str {
+        "Death Blossom"
+    }
+
+    fn difficulty(&self) -> f32 {
+        8.2
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board_death_blossom(board).iter().map(AlsChain::deduction).collect()
+    }
+}
+
+/// Every almost locked set in every unit, each listed once.
+pub fn collect(board: &Board) -> Vec<Als> {
+    let mut sets: Vec<_> = units(board)
+        .iter()
+        .flat_map(|unit| almost_locked_sets(board, &unit.cells(board.size), MAX_ALS_SIZE))
+        .map(|(cells, vals)| Als { cells, vals })
+        .collect();
+    sets.sort_unstable();
+    sets.dedup();
+    sets
+}
+
+/// Finds pairs of sets joined by a restricted common digit `x`: as `x` can only be in one of them, the other is locked
+/// and any other shared digit `z` must be in one of the two. With two restricted commons both sets are locked.
+pub fn from_board_xz(board: &Board) -> Rc<[AlsChain]> {
+    let sees = Visibility::new(board);
+    let sets = collect(board);
+    let mut out = vec![];
+
+    for (i, a) in sets.iter().enumerate() {
+        for b in &sets[i + 1..] {
+            let links = restricted_commons(board, &sees, a, b);
+            let chain = |kind, eliminated| AlsChain {
+                kind,
+                sets: vec![a.clone(), b.clone()],
+                stem: None,
+                links,
+                eliminated,
+            };
+
+            match links.count_ones() {
+                1 => {
+                    let eliminated: Vec<_> = digits(a.vals & b.vals & !links).flat_map(|val| seeing_all(board, &sees, &[a, b], val)).collect();
+                    if !eliminated.is_empty() {
+                        out.push(chain(AlsKind::Xz, eliminated));
+                    }
+                }
+                2 => {
+                    let mut eliminated: Vec<_> = digits(links).flat_map(|val| seeing_all(board, &sees, &[a, b], val)).collect();
+                    for (set, other) in [(a, b), (b, a)] {
+                        eliminated.extend(
+                            digits(set.vals & !links)
+                                .flat_map(|val| seeing_all(board, &sees, &[set], val))
+                                .filter(|(cell, _)| !other.cells.contains(cell)),
+                        );
+                    }
+                    if !eliminated.is_empty() {
+                        out.push(chain(AlsKind::DoublyLinkedXz, eliminated));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Finds sets `A` and `B` each joined to a third set `C` by a different restricted common digit. Were both links to
+/// leave their digit out of `C` it would be two digits short, so one of `A` or `B` is locked and a digit `z` shared by
+/// both must be in one of them.
+pub fn from_board_xy_wing(board: &Board) -> Rc<[AlsChain]> {
+    let sees = Visibility::new(board);
+    let sets = collect(board);
+    let mut out = vec![];
+
+    for c in &sets {
+        let linked: Vec<_> = sets
+            .iter()
+            .filter(|set| disjoint(set, c))
+            .map(|set| (set, restricted_commons(board, &sees, set, c)))
+            .filter(|(_, links)| *links != 0)
+            .collect();
+
+        for (i, (a, links_a)) in linked.iter().enumerate() {
+            for (b, links_b) in &linked[i + 1..] {
+                if !disjoint(a, b) {
+                    continue;
+                }
+                // Pick a different digit for each link
+                for x in digits(*links_a) {
+                    for y in digits(*links_b & !(1 << x)) {
+                        let eliminated: Vec<_> = digits(a.vals & b.vals & !(1 << x | 1 << y))
+                            .flat_map(|val| seeing_all(board, &sees, &[a, b], val))
+                            .filter(|(cell, _)| !c.cells.contains(cell))
+                            .collect();
+                        if !eliminated.is_empty() {
+                            out.push(AlsChain {
+                                kind: AlsKind::XyWing,
+                                sets: vec![(*a).clone(), (*b).clone(), c.clone()],
+                                stem: None,
+                                links: 1 << x | 1 << y,
+                                eliminated,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Finds a stem cell whose every candidate `d` sees all the `d` cells of one of the sets, its petals. Whichever digit
+/// the stem takes locks its petal, so a digit `z` in every petal must be in one of them.
+pub fn from_board_death_blossom(board: &Board) -> Rc<[AlsChain]> {
+    let sees = Visibility::new(board);
+    let sets = collect(board);
+    let mut out = vec![];
+
+    for stem in cells(board).iter().copied().filter(|cell| board[*cell].count_ones() > 1) {
+        let stem_vals = board[stem];
+        for val in digits(default_cell(board.size) & !stem_vals) {
+            // The possible petals for each stem candidate, all holding the eliminated digit
+            let petals: Vec<Vec<&Als>> = digits(stem_vals)
+                .map(|d| {
+                    sets.iter()
+                        .filter(|set| is_set!(set.vals, val) && is_set!(set.vals, d) && !set.cells.contains(&stem))
+                        .filter(|set| holding(board, set, d).all(|cell| sees.get(cell, stem)))
+                        .collect()
+                })
+                .collect();
+            if petals.iter().any(Vec::is_empty) {
+                continue;
+            }
+
+            let targets: Vec<_> = cells(board).iter().copied().filter(|cell| *cell != stem && is_set!(board[*cell], val)).collect();
+            let mut chosen = vec![];
+            if let Some(eliminated) = pick_petals(board, &sees, &petals, &mut chosen, targets, val) {
+                out.push(AlsChain {
+                    kind: AlsKind::DeathBlossom,
+                    sets: chosen.into_iter().cloned().collect(),
+                    stem: Some(stem),
+                    links: stem_vals,
+                    eliminated,
+                });
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Chooses one petal per stem candidate, keeping only the targets which see every `val` in the petals so far.
+fn pick_petals<'a>(board: &Board, sees: &Visibility, petals: &[Vec<&'a Als>], chosen: &mut Vec<&'a Als>, targets: Vec<Cell>, val: u32) -> Option<Vec<(Cell, u32)>> {
+    if targets.is_empty() {
+        return None;
+    }
+    let Some((options, rest)) = petals.split_first() else {
+        return Some(targets.into_iter().map(|cell| (cell, val)).collect());
+    };
+
+    for petal in options {
+        let remaining = targets
+            .iter()
+            .copied()
+            .filter(|cell| !petal.cells.contains(cell) && holding(board, petal, val).all(|held| sees.get(held, *cell)))
+            .collect();
+        chosen.push(petal);
+        if let Some(eliminated) = pick_petals(board, sees, rest, chosen, remaining, val) {
+            return Some(eliminated);
+        }
+        chosen.pop();
+    }
+    None
+}
+
+/// Digits common to both sets where every cell holding the digit in one sees every such cell in the other, so at most
+/// one of the sets can hold it.
+fn restricted_commons(board: &Board, sees: &Visibility, a: &Als, b: &Als) -> u32 {
+    if !disjoint(a, b) {
+        return 0;
+    }
+    digits(a.vals & b.vals)
+        .filter(|val| holding(board, a, *val).all(|x| holding(board, b, *val).all(|y| sees.get(x, y))))
+        .fold(0, |acc, val| acc | 1 << val)
+}
+
+/// Candidates `val` outside the sets which see every cell holding it in all of them.
+fn seeing_all(board: &Board, sees: &Visibility, sets: &[&Als], val: u32) -> Vec<(Cell, u32)> {
+    cells(board)
+        .iter()
+        .filter(|cell| is_set!(board[**cell], val) && sets.iter().all(|set| !set.cells.contains(cell)))
+        .filter(|cell| sets.iter().all(|set| holding(board, set, val).all(|held| sees.get(held, **cell))))
+        .map(|cell| (*cell, val))
+        .collect()
+}
+
+fn holding<'a>(board: &'a Board, set: &'a Als, val: u32) -> impl Iterator<Item = Cell> + 'a {
+    set.cells.iter().copied().filter(move |cell| is_set!(board[*cell], val))
+}
+
+fn disjoint(a: &Als, b: &Als) -> bool {
+    a.cells.iter().all(|cell| !b.cells.contains(cell))
+}
+
+impl AlsKind {
+    pub fn description(self) -> Option<&'static str> {
+        match self {
+            AlsKind::DoublyLinkedXz => Some("doubly linked"),
+            AlsKind::Xz | AlsKind::XyWing | AlsKind::DeathBlossom => None,
+        }
+    }
+}
+
+impl AlsChain {
+    pub fn deduction(&self) -> Deduction {
+        let mut cells: Vec<_> = self.stem.into_iter().collect();
+        cells.extend(self.sets.iter().flat_map(|set| set.cells.iter().copied()));
+        let digits = self.eliminated.iter().fold(self.links, |acc, (_, val)| acc | 1 << val);
+
+        let mut deduction = Deduction::new(cells, digits);
+        deduction.rule = self.kind.description();
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn als_xz_eliminates_the_shared_digit() {
+        // r1c1 {1, 2} and the set r1c5 {1, 3}, r2c5 {2, 3} share the restricted 1, so one of them holds 2
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2;
+        board[Cell { row: 0, col: 4 }] = 1 << 1 | 1 << 3;
+        board[Cell { row: 1, col: 4 }] = 1 << 2 | 1 << 3;
+
+        let chains = from_board_xz(&board);
+        assert!(chains.iter().all(|chain| chain.kind == AlsKind::Xz));
+        let mut eliminated: Vec<_> = chains.iter().flat_map(|chain| chain.eliminated.clone()).collect();
+        eliminated.sort_unstable();
+        eliminated.dedup();
+
+        // Cells seeing both r1c1 and r2c5
+        let expected = [(0, 3), (0, 5), (1, 0), (1, 1), (1, 2)].map(|(row, col)| (Cell { row, col }, 2));
+        assert_eq!(eliminated, expected);
+    }
+}
@@ -4,17 +4,29 @@ use std::{
 };
 
 use crate::{
-    colouring,
-    defaults::{default_cell, default_regions},
-    format, hiddens, intersections,
+    defaults::{default_cell, default_region_bounds, default_regions},
+    error::SolveError,
+    hiddens::HiddenSingles,
     misc::is_set,
-    nakeds, rectangles, xwings, ywings, SIZE,
+    nakeds::{NAKED_SINGLE, NAKED_SINGLE_DIFFICULTY},
+    search,
+    solver::{Solver, Technique},
+    step::{Deduction, Step},
 };
 
 #[derive(Debug, Clone)]
 pub struct Board {
     pub regions: Vec<Region>,
-    pub cells: [[u16; SIZE]; SIZE],
+    pub size: usize,
+    pub box_width: usize,
+    pub box_height: usize,
+    pub cells: Vec<Vec<u32>>,
+    /// The digits the puzzle started with, 0 where a cell was empty. Only known for boards built by `from_givens`.
+    pub givens: Option<Givens>,
+    pub log: Vec<Step>,
+    /// Set once the puzzle is proven to have a single solution. Techniques which rely
+    /// on uniqueness must not run while this is false.
+    pub unique: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -24,7 +36,7 @@ pub struct Cell {
 }
 
 pub type Region = Vec<Cell>;
-type Anyhow = Option<()>;
+pub type Givens = Vec<Vec<u32>>;
 
 macro_rules! get_regions_with_cell {
     ($board:ident, $cell:expr) => {
@@ -41,119 +53,122 @@ macro_rules! get_regions_with_cells {
 pub(crate) use get_regions_with_cells;
 
 impl Board {
-    pub fn solve(&mut self) {
-        loop {
-            if self.place_hidden_single() {
-                continue;
-            }
-            if self.clean_nakeds2() {
-                continue;
-            }
-            if self.clean_hiddens2() {
-                continue;
-            }
-            if self.clean_nakeds3() {
-                continue;
-            }
-            if self.clean_hiddens3() {
-                continue;
-            }
-            if self.clean_xwings2() {
-                continue;
-            }
-            if self.clean_ywings() {
-                continue;
-            }
-            if self.clean_rectangles() {
-                continue;
-            }
-            if self.clean_intersections() {
-                continue;
-            }
-            if self.clean_nakeds4() {
-                continue;
-            }
-            if self.clean_hiddens4() {
-                continue;
-            }
-            if self.clean_xwings3() {
-                continue;
-            }
-            if self.clean_colouring() {
-                continue;
-            }
-            if self.clean_xwings4() {
-                continue;
-            }
+    pub fn solve(&mut self) -> Result<(), SolveError> {
+        Solver::default().solve(self)
+    }
 
-            break;
-        }
+    /// Counts solutions up to `limit`; a result equal to `limit` means there are at least that many.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        search::count(self, limit)
     }
 
-    pub fn new_custom_regions(regions: Vec<Region>) -> Self {
+    pub fn new_custom_regions(size: usize, regions: Vec<Region>) -> Self {
+        let (box_width, box_height) = default_region_bounds(size).unwrap_or((size, 1));
         Board {
             regions,
-            cells: [[default_cell(); SIZE]; SIZE],
+            size,
+            box_width,
+            box_height,
+            cells: vec![vec![default_cell(size); size]; size],
+            givens: None,
+            log: vec![],
+            unique: false,
         }
     }
 
-    pub fn new() -> Self {
+    pub fn new(size: usize) -> Self {
+        let (box_width, box_height) = default_region_bounds(size).expect("Default regions for specified size does not exist");
         Board {
-            regions: default_regions(),
-            cells: [[default_cell(); SIZE]; SIZE],
+            regions: default_regions(size),
+            size,
+            box_width,
+            box_height,
+            cells: vec![vec![default_cell(size); size]; size],
+            givens: None,
+            log: vec![],
+            unique: false,
+        }
+    }
+
+    /// Builds a board from a grid of givens, where 0 marks an empty cell.
+    pub fn from_givens(givens: &Givens) -> Result<Self, SolveError> {
+        let size = givens.len();
+        if default_region_bounds(size).is_none() || givens.iter().any(|row| row.len() != size) {
+            return Err(SolveError::UnsupportedSize { size });
+        }
+        let mut board = Board::new(size);
+
+        for (row, vals) in givens.iter().enumerate() {
+            for (col, val) in vals.iter().enumerate() {
+                if *val == 0 {
+                    continue;
+                }
+                let cell = Cell { row, col };
+                if !is_set!(board[cell], val) {
+                    return Err(SolveError::InvalidGiven { cell, val: *val });
+                }
+                board.place_digit(*val, cell)?;
+            }
         }
+        board.givens = Some(givens.clone());
+
+        Ok(board)
     }
 
     pub fn is_solved(&self) -> bool {
         self.cells.iter().all(|row| row.iter().all(|cell| cell.is_power_of_two()))
     }
 
-    pub fn get_cell_coords(&self, row: usize, col: usize) -> Option<u16> {
+    pub fn get_cell_coords(&self, row: usize, col: usize) -> Option<u32> {
         self.cells.get(row)?.get(col).copied()
     }
 
-    pub fn get_mut_cell_coords(&mut self, row: usize, col: usize) -> Option<&mut u16> {
+    pub fn get_mut_cell_coords(&mut self, row: usize, col: usize) -> Option<&mut u32> {
         self.cells.get_mut(row)?.get_mut(col)
     }
 
-    pub fn place_digit(&mut self, val: u16, cell: Cell) {
-        if self[cell] & (1 << val) == 0 {
-            panic!("Attempted to place digit in cell where it is not a candidate")
+    pub fn place_digit(&mut self, val: u32, cell: Cell) -> Result<(), SolveError> {
+        if !is_set!(self[cell], val) {
+            return Err(SolveError::Contradiction {
+                cell,
+                reason: "attempted to place digit in cell where it is not a candidate",
+            });
         }
         self[cell] = 1 << val;
 
-        self.clean_col(cell.col, &[cell.row], val);
-        self.clean_row(cell.row, &[cell.col], val);
-        self.clean_reg(cell, &[cell], val);
+        self.clean_col(cell.col, &[cell.row], val)?;
+        self.clean_row(cell.row, &[cell.col], val)?;
+        self.clean_reg(cell, &[cell], val)?;
+        Ok(())
     }
 
-    pub fn clean_row(&mut self, row: usize, ignore: &[usize], val: u16) -> bool {
+    pub fn clean_row(&mut self, row: usize, ignore: &[usize], val: u32) -> Result<bool, SolveError> {
         let mut has_changed = false;
-        for i in 0..SIZE {
+        for i in 0..self.size {
             if ignore.contains(&i) {
                 continue;
             }
-            if self.clean_cell(Cell { row, col: i }, val) {
+            if self.clean_cell(Cell { row, col: i }, val)? {
                 has_changed = true;
             }
         }
-        has_changed
+        Ok(has_changed)
     }
 
-    pub fn clean_col(&mut self, col: usize, ignore: &[usize], val: u16) -> bool {
+    pub fn clean_col(&mut self, col: usize, ignore: &[usize], val: u32) -> Result<bool, SolveError> {
         let mut has_changed = false;
-        for i in 0..SIZE {
+        for i in 0..self.size {
             if ignore.contains(&i) {
                 continue;
             }
-            if self.clean_cell(Cell { row: i, col }, val) {
+            if self.clean_cell(Cell { row: i, col }, val)? {
                 has_changed = true;
             }
         }
-        has_changed
+        Ok(has_changed)
     }
 
-    pub fn clean_reg(&mut self, cell: Cell, ignore: &[Cell], val: u16) -> bool {
+    pub fn clean_reg(&mut self, cell: Cell, ignore: &[Cell], val: u32) -> Result<bool, SolveError> {
         let mut has_changed = false;
         let mut regions = vec![];
         for region in self.regions.iter().filter(|reg| reg.contains(&cell)) {
@@ -164,82 +179,98 @@ impl Board {
                 if ignore.contains(&cell) {
                     continue;
                 }
-                if self.clean_cell(cell, val) {
+                if self.clean_cell(cell, val)? {
                     has_changed = true;
                 }
             }
         }
-        has_changed
+        Ok(has_changed)
     }
 
-    pub fn clean_cell(&mut self, cell: Cell, val: u16) -> bool {
+    pub fn clean_cell(&mut self, cell: Cell, val: u32) -> Result<bool, SolveError> {
         let mut has_changed = false;
         let mut last_val = None;
         let cell_val = &mut self[cell];
-        assert!(*cell_val != 1 << val, "Cell {cell} has no possibilities");
+        if *cell_val == 1 << val {
+            return Err(SolveError::NoCandidates { cell });
+        }
         if is_set!(*cell_val, val) {
             has_changed = true;
             *cell_val &= !(1 << val);
-            #[allow(clippy::cast_possible_truncation)]
             if cell_val.is_power_of_two() {
-                last_val = Some(cell_val.trailing_zeros() as u16);
+                last_val = Some(cell_val.trailing_zeros());
             }
         }
         if let Some(new_val) = last_val {
-            self.place_digit(new_val, cell);
+            let mut deduction = Deduction::default();
+            deduction.placed.push((cell, new_val));
+            self.log.push(Step {
+                technique: NAKED_SINGLE,
+                difficulty: NAKED_SINGLE_DIFFICULTY,
+                deduction,
+            });
+            self.place_digit(new_val, cell)?;
         }
-        has_changed
+        Ok(has_changed)
     }
 
-    pub fn place_hidden_single(&mut self) -> bool {
-        let size = SIZE;
+    pub fn place_hidden_single(&mut self) -> Result<bool, SolveError> {
+        let size = self.size;
         for cell in (0..size).flat_map(|row| (0..size).map(move |col| Cell { row, col })) {
-            if self.place_if_hidden_single(cell).is_some() {
-                return true;
+            if self.place_if_hidden_single(cell)? {
+                return Ok(true);
             }
         }
 
-        false
+        Ok(false)
+    }
+
+    pub fn place_if_hidden_single(&mut self, cell: Cell) -> Result<bool, SolveError> {
+        #[allow(clippy::cast_possible_truncation)]
+        if let Some(val) = self.get_hidden_single(cell) {
+            let mut deduction = Deduction::default();
+            deduction.placed.push((cell, val as u32));
+            self.log.push(Step {
+                technique: HiddenSingles.name(),
+                difficulty: HiddenSingles.difficulty(),
+                deduction,
+            });
+            self.place_digit(val as u32, cell)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
     }
 
-    pub fn place_if_hidden_single(&mut self, cell: Cell) -> Anyhow {
+    pub fn get_hidden_single(&self, cell: Cell) -> Option<usize> {
         if self[cell].is_power_of_two() {
             return None;
         }
-        let single = {
-            let cell_val = self[cell];
-            let in_row = self.get_row_nums(cell.row, &[cell.col])?;
-            let possible = (0..SIZE).find(|val| is_set!(cell_val, val) && !is_set!(in_row, val));
+        let cell_val = self[cell];
+        let in_row = self.get_row_nums(cell.row, &[cell.col])?;
+        let possible = (1..=self.size).find(|val| is_set!(cell_val, val) && !is_set!(in_row, val));
+        if possible.is_some() {
+            possible
+        } else {
+            let in_col = self.get_col_nums(cell.col, &[cell.row])?;
+            let possible = (1..=self.size).find(|val| is_set!(cell_val, val) && !is_set!(in_col, val));
             if possible.is_some() {
                 possible
             } else {
-                let in_col = self.get_col_nums(cell.col, &[cell.row])?;
-                let possible = (0..SIZE).find(|val| is_set!(cell_val, val) && !is_set!(in_col, val));
+                let in_reg = self.get_reg_nums(cell, &[cell]);
+                let possible = (1..=self.size).find(|val| is_set!(cell_val, val) && !is_set!(in_reg, val));
                 if possible.is_some() {
                     possible
                 } else {
-                    let in_reg = self.get_reg_nums(cell, &[cell]);
-                    let possible = (0..SIZE).find(|val| is_set!(cell_val, val) && !is_set!(in_reg, val));
-                    if possible.is_some() {
-                        possible
-                    } else {
-                        None
-                    }
+                    None
                 }
             }
-        };
-        #[allow(clippy::cast_possible_truncation)]
-        if let Some(val) = single {
-            self.place_digit(val as u16, cell);
-            Some(())
-        } else {
-            None
         }
     }
 
-    pub fn get_row_nums(&self, row: usize, ignore: &[usize]) -> Option<u16> {
+    pub fn get_row_nums(&self, row: usize, ignore: &[usize]) -> Option<u32> {
         let mut digits = 0;
-        for col in 0..9 {
+        for col in 0..self.size {
             if ignore.contains(&col) {
                 continue;
             }
@@ -248,9 +279,9 @@ impl Board {
         Some(digits)
     }
 
-    pub fn get_col_nums(&self, col: usize, ignore: &[usize]) -> Option<u16> {
+    pub fn get_col_nums(&self, col: usize, ignore: &[usize]) -> Option<u32> {
         let mut digits = 0;
-        for row in 0..9 {
+        for row in 0..self.size {
             if ignore.contains(&row) {
                 continue;
             }
@@ -259,7 +290,7 @@ impl Board {
         Some(digits)
     }
 
-    pub fn get_reg_nums(&self, cell: Cell, ignore: &[Cell]) -> u16 {
+    pub fn get_reg_nums(&self, cell: Cell, ignore: &[Cell]) -> u32 {
         let mut digits = 0;
         for region in get_regions_with_cell!(self, &cell) {
             for cell in region {
@@ -273,235 +304,32 @@ impl Board {
         digits
     }
 
-    pub fn clean_nakeds2(&mut self) -> bool {
-        let groups = nakeds::from_board2(self);
-
-        let mut has_changed = false;
-        for group in groups.iter() {
-            for val in (1..=SIZE).filter_map(|d| {
-                if is_set!(group.vals, d) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    Some(d as u16)
-                } else {
-                    None
-                }
-            }) {
-                if group.relation.row {
-                    has_changed = self.clean_row(group.cells[0].row, &group.cells.iter().map(|cell| cell.col).collect::<Box<[_]>>()[..], val) || has_changed;
-                } else if group.relation.col {
-                    has_changed = self.clean_col(group.cells[0].col, &group.cells.iter().map(|cell| cell.row).collect::<Box<[_]>>()[..], val) || has_changed;
-                }
-                if group.relation.reg {
-                    has_changed = self.clean_reg(group.cells[0], &group.cells, val) || has_changed;
-                }
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_nakeds3(&mut self) -> bool {
-        let groups = nakeds::from_board3(self);
-
-        let mut has_changed = false;
-        for group in groups.iter() {
-            for val in (1..=SIZE).filter_map(|d| {
-                if is_set!(group.vals, d) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    Some(d as u16)
-                } else {
-                    None
-                }
-            }) {
-                if group.relation.row {
-                    has_changed = self.clean_row(group.cells[0].row, &group.cells.iter().map(|cell| cell.col).collect::<Box<[_]>>()[..], val) || has_changed;
-                } else if group.relation.col {
-                    has_changed = self.clean_col(group.cells[0].col, &group.cells.iter().map(|cell| cell.row).collect::<Box<[_]>>()[..], val) || has_changed;
-                }
-                if group.relation.reg {
-                    has_changed = self.clean_reg(group.cells[0], &group.cells, val) || has_changed;
-                }
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_nakeds4(&mut self) -> bool {
-        let groups = nakeds::from_board4(self);
-
-        let mut has_changed = false;
-        for group in groups.iter() {
-            for val in (1..=SIZE).filter_map(|d| {
-                if is_set!(group.vals, d) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    Some(d as u16)
-                } else {
-                    None
-                }
-            }) {
-                if group.relation.row {
-                    has_changed = self.clean_row(group.cells[0].row, &group.cells.iter().map(|cell| cell.col).collect::<Box<[_]>>()[..], val) || has_changed;
-                } else if group.relation.col {
-                    has_changed = self.clean_col(group.cells[0].col, &group.cells.iter().map(|cell| cell.row).collect::<Box<[_]>>()[..], val) || has_changed;
-                }
-                if group.relation.reg {
-                    has_changed = self.clean_reg(group.cells[0], &group.cells, val) || has_changed;
-                }
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_hiddens2(&mut self) -> bool {
-        let mut has_changed = false;
-        for group in hiddens::from_board2(self) {
-            for cell in group.cells {
-                if !has_changed && self[cell].count_ones() > 2 {
-                    has_changed = true;
-                }
-                self[cell] &= group.vals;
-            }
-        }
-        has_changed
-    }
-
-    pub fn clean_hiddens3(&mut self) -> bool {
-        let mut has_changed = false;
-        for group in hiddens::from_board3(self) {
-            for cell in group.cells {
-                if !has_changed && self[cell].count_ones() > 3 {
-                    has_changed = true;
-                }
-                self[cell] &= group.vals;
-            }
-        }
-        has_changed
-    }
-
-    pub fn clean_hiddens4(&mut self) -> bool {
-        let mut has_changed = false;
-        for group in hiddens::from_board4(self) {
-            for cell in group.cells {
-                if !has_changed && self[cell].count_ones() > 4 {
-                    has_changed = true;
-                }
-                self[cell] &= group.vals;
-            }
-        }
-        has_changed
-    }
-
-    pub fn clean_xwings2(&mut self) -> bool {
-        let xwings = xwings::from_board2(self);
-
-        let mut has_changed = false;
-        for xwing in xwings.iter() {
-            if xwing.clear_rows {
-                for row in xwing.rows {
-                    has_changed = self.clean_row(row, &xwing.cols, xwing.val) || has_changed;
-                }
-            } else {
-                for col in xwing.cols {
-                    has_changed = self.clean_col(col, &xwing.rows, xwing.val) || has_changed;
-                }
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_xwings3(&mut self) -> bool {
-        let xwings = xwings::from_board3(self);
+    /// Records `step` in the log and carries out its placements and eliminations.
+    /// Deductions which no longer change the board are dropped first, so a step
+    /// that has become redundant is neither logged nor reported as progress.
+    pub fn apply(&mut self, mut step: Step) -> Result<bool, SolveError> {
+        let deduction = &mut step.deduction;
+        deduction.placed.retain(|(cell, val)| self[*cell] != 1 << val);
+        deduction.eliminated.retain(|(cell, val)| is_set!(self[*cell], val));
+        deduction.eliminated.sort_unstable();
+        deduction.eliminated.dedup();
 
-        let mut has_changed = false;
-        for xwing in xwings.iter() {
-            if xwing.clear_rows {
-                for row in xwing.rows {
-                    has_changed = self.clean_row(row, &xwing.cols, xwing.val) || has_changed;
-                }
-            } else {
-                for col in xwing.cols {
-                    has_changed = self.clean_col(col, &xwing.rows, xwing.val) || has_changed;
-                }
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_xwings4(&mut self) -> bool {
-        let xwings = xwings::from_board4(self);
-
-        let mut has_changed = false;
-        for xwing in xwings.iter() {
-            if xwing.clear_rows {
-                for row in xwing.rows {
-                    has_changed = self.clean_row(row, &xwing.cols, xwing.val) || has_changed;
-                }
-            } else {
-                for col in xwing.cols {
-                    has_changed = self.clean_col(col, &xwing.rows, xwing.val) || has_changed;
-                }
-            }
+        if deduction.is_empty() {
+            return Ok(false);
         }
 
-        has_changed
-    }
-
-    pub fn clean_ywings(&mut self) -> bool {
-        let ywings = ywings::from_board(self);
+        let placed = deduction.placed.clone();
+        let eliminated = deduction.eliminated.clone();
+        self.log.push(step);
 
-        let mut has_changed = false;
-        for ywing in ywings.iter() {
-            has_changed = self.clean_cell(ywing.target, ywing.val) || has_changed;
+        for (cell, val) in placed {
+            self.place_digit(val, cell)?;
         }
-
-        has_changed
-    }
-
-    pub fn clean_intersections(&mut self) -> bool {
-        let intersections = intersections::from_board(self);
-
-        let mut has_changed = false;
-        for intersection in intersections.iter() {
-            for cell in &intersection.cells {
-                has_changed = self.clean_cell(*cell, intersection.val) || has_changed;
-            }
-        }
-
-        has_changed
-    }
-
-    pub fn clean_rectangles(&mut self) -> bool {
-        if let Some((cells, val)) = rectangles::from_board(self) {
-            for cell in cells {
-                self.clean_cell(cell, val);
-            }
-
-            true
-        } else {
-            false
-        }
-    }
-
-    pub fn clean_colouring(&mut self) -> bool {
-        let colour_map = colouring::from_board(self);
-
-        if colour_map.eliminated.is_empty() && colour_map.placed.is_empty() {
-            return false;
-        }
-
-        for (cell, val) in colour_map.placed {
-            self.place_digit(val, cell);
-        }
-
-        for (cell, val) in colour_map.eliminated {
-            self.clean_cell(cell, val);
+        for (cell, val) in eliminated {
+            self.clean_cell(cell, val)?;
         }
 
-        true
+        Ok(true)
     }
 }
 
@@ -512,7 +340,7 @@ impl Cell {
 }
 
 impl Index<Cell> for Board {
-    type Output = u16;
+    type Output = u32;
 
     fn index(&self, index: Cell) -> &Self::Output {
         &self.cells[index.row][index.col]
@@ -527,6 +355,6 @@ impl IndexMut<Cell> for Board {
 
 impl Display for Cell {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.col, self.row)
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
     }
 }
@@ -0,0 +1,141 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{cells, digits, is_set, units},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct Bugs;
+
+/// A bivalue universal grave: without the extra candidates every unsolved cell would be bivalue and every digit would
+/// appear twice in each unit, a pattern with either no solution or several. So at least one extra candidate is true.
+#[derive(Debug, Clone)]
+pub struct Bug {
+    /// The unsolved cells with more than two candidates.
+    pub cells: Vec<Cell>,
+    pub extras: Vec<(Cell, u32)>,
+}
+
+impl Technique for Bugs {
+    fn name(&self) -> &'static str {
+        "BUG"
+    }
+
+    fn difficulty(&self) -> f32 {
+        5.6
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board).iter().map(|bug| bug.deduction(board)).collect()
+    }
+
+    fn assumes_uniqueness(&self) -> bool {
+        true
+    }
+}
+
+pub fn from_board(board: &Board) -> Rc<[Bug]> {
+    let unsolved: Vec<_> = cells(board).iter().copied().filter(|cell| board[*cell].count_ones() > 1).collect();
+    let poly: Vec<_> = unsolved.iter().copied().filter(|cell| board[*cell].count_ones() > 2).collect();
+    if poly.is_empty() {
+        return Rc::new([]);
+    }
+
+    // A candidate is extra when its digit appears more than twice in each of the cell's units
+    let units = units(board);
+    let extras: Vec<_> = poly
+        .iter()
+        .flat_map(|cell| digits(board[*cell]).map(move |val| (*cell, val)))
+        .filter(|(cell, val)| {
+            units
+                .iter()
+                .map(|unit| unit.cells(board.size))
+                .filter(|unit_cells| unit_cells.contains(cell))
+                .all(|unit_cells| unit_cells.iter().filter(|c| board[**c].count_ones() > 1 && is_set!(board[**c], val)).count() > 2)
+        })
+        .collect();
+
+    let remaining = |cell: &Cell| extras.iter().filter(|(c, _)| c == cell).fold(board[*cell], |acc, (_, val)| acc & !(1 << val));
+    if unsolved.iter().any(|cell| remaining(cell).count_ones() != 2) {
+        return Rc::new([]);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let is_grave = units.iter().all(|unit| {
+        let unit_cells = unit.cells(board.size);
+        (1..=board.size).map(|val| val as u32).all(|val| {
+            let count = unit_cells.iter().filter(|c| board[**c].count_ones() > 1 && is_set!(remaining(c), val)).count();
+            count == 0 || count == 2
+        })
+    });
+    if !is_grave {
+        return Rc::new([]);
+    }
+
+    Rc::new([Bug { cells: poly, extras }])
+}
+
+impl Bug {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let digits = self.extras.iter().fold(0, |acc, (_, val)| acc | 1 << val);
+        let mut deduction = Deduction::new(self.cells.clone(), digits);
+
+        if let [extra] = self.extras[..] {
+            deduction.rule = Some("BUG+1");
+            deduction.placed.push(extra);
+            return deduction;
+        }
+        deduction.rule = Some("BUG+n");
+
+        // With every extra in one cell, that cell must take one of them
+        if let [cell] = self.cells[..] {
+            #[allow(clippy::cast_possible_truncation)]
+            deduction.eliminated.extend(
+                (1..=board.size)
+                    .map(|val| val as u32)
+                    .filter(|val| is_set!(board[cell], val) && !self.extras.contains(&(cell, *val)))
+                    .map(|val| (cell, val)),
+            );
+        }
+
+        // With every extra the same digit, that digit goes in one of them
+        if digits.count_ones() == 1 {
+            let val = digits.trailing_zeros();
+            deduction.eliminated.extend(
+                cells(board)
+                    .iter()
+                    .filter(|cell| is_set!(board[**cell], val) && !self.extras.contains(&(**cell, val)))
+                    .filter(|cell| self.extras.iter().all(|(extra, _)| extra.can_see(board, cell)))
+                    .map(|cell| (*cell, val)),
+            );
+        }
+
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bug_plus_one_places_the_extra_candidate() {
+        // Every cell holds its digit and the one it swaps with, 1 with 2 and 3 with 4, except r1c1, which also holds 3
+        let grid = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+        let mut board = Board::new(4);
+        for (row, vals) in grid.iter().enumerate() {
+            for (col, val) in vals.iter().enumerate() {
+                let swapped = if val % 2 == 1 { val + 1 } else { val - 1 };
+                board[Cell { row, col }] = 1 << val | 1 << swapped;
+            }
+        }
+        board[Cell { row: 0, col: 0 }] |= 1 << 3;
+
+        let deductions = Bugs.find(&board);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].rule, Some("BUG+1"));
+        assert_eq!(deductions[0].placed, [(Cell { row: 0, col: 0 }, 3)]);
+    }
+}
@@ -1,274 +1,328 @@
-use std::cmp::{max, min};
+use std::rc::Rc;
 
 use crate::{
-    board::{get_regions_with_cells, Board, Cell},
-    misc::{cells, is_set},
-    SIZE,
+    board::{Board, Cell},
+    misc::{bivalue_cells, cells, conjugate_pairs, digits, units},
+    solver::Technique,
+    step::Deduction,
 };
 
-#[derive(Debug, Clone)]
-struct Colouring<'a> {
-    nodes: Vec<ColourNode>,
-    board: &'a Board,
-}
+pub struct SimpleColouring;
 
-#[derive(Debug, Clone)]
-struct ColourNode {
-    cell: Cell,
-    val: u16,
-    state: State,
-    connects_to: Vec<(usize, bool)>, // if true, this or the connected must be true, else both can be false
+pub struct Medusa;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    TwiceInCell,
+    TwiceInUnit,
+    TwoColoursInCell,
+    TwoColoursElsewhere,
+    TwoColoursUnitAndCell,
+    CellEmptiedByColour,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum State {
-    None,
-    True,
-    False,
+/// Candidates joined by strong links, split into two colours of which exactly one is true.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    pub nodes: Vec<(Cell, u32, bool)>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ColourMap {
-    pub eliminated: Vec<(Cell, u16)>,
-    pub placed: Vec<(Cell, u16)>,
+    pub rule: Rule,
+    pub cells: Vec<Cell>,
+    pub digits: u32,
+    pub eliminated: Vec<(Cell, u32)>,
+    pub placed: Vec<(Cell, u32)>,
 }
 
-pub fn from_board(board: &Board) -> ColourMap {
-    let mut colouring = Colouring::new(board);
-    let cells = cells();
-
-    for cell in cells.iter() {
-        let val = board[*cell];
-        if val.count_ones() == 2 {
-            colouring.add_cell(*cell, val);
-        }
+impl Technique for SimpleColouring {
+    fn name(&self) -> &'static str {
+        "Simple Colouring"
     }
 
-    for pair in cells.iter().zip(1..).flat_map(|(a, i)| cells[i..].iter().map(move |b| (a, b))) {
-        let overlap = board[*pair.0] & board[*pair.1];
-        let mut is_added = 0;
-        if pair.0.row == pair.1.row {
-            let mut others = 0;
-            for col in 0..SIZE {
-                if col != pair.0.col && col != pair.1.col {
-                    others |= board.get_cell_coords(pair.0.row, col).unwrap();
-                }
-            }
-            for d in 1..=SIZE {
-                if overlap & 1 << d > 0 && others & 1 << d == 0 {
-                    #[allow(clippy::cast_possible_truncation)]
-                    colouring.add_pair(*pair.0, *pair.1, d as u16);
-                    is_added |= 1 << d;
-                }
-            }
-        } else if pair.0.col == pair.1.col {
-            let mut others = 0;
-            for row in 0..SIZE {
-                if row != pair.0.row && row != pair.1.row {
-                    others |= board.get_cell_coords(row, pair.0.col).unwrap();
-                }
-            }
-            for d in 1..=SIZE {
-                if is_added & 1 << d == 0 && overlap & 1 << d > 0 && others & 1 << d == 0 {
-                    #[allow(clippy::cast_possible_truncation)]
-                    colouring.add_pair(*pair.0, *pair.1, d as u16);
-                    is_added |= 1 << d;
-                }
-            }
-        }
-        for reg in get_regions_with_cells!(board, &[pair.0, pair.1]) {
-            let mut others = 0;
-            for cell in reg {
-                if cell != pair.0 && cell != pair.1 {
-                    others |= board[*cell];
-                }
-            }
-            for d in 1..=SIZE {
-                if is_added & 1 << d == 0 && overlap & 1 << d > 0 && others & 1 << d == 0 {
-                    #[allow(clippy::cast_possible_truncation)]
-                    colouring.add_pair(*pair.0, *pair.1, d as u16);
-                    is_added |= 1 << d;
-                }
-            }
-        }
+    fn difficulty(&self) -> f32 {
+        7.0
     }
 
-    colouring.dedup();
-    colouring.connect();
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board, false).iter().map(ColourMap::deduction).collect()
+    }
+}
 
-    let possible = colouring.get_possible_colourings();
+impl Technique for Medusa {
+    fn name(&self) -> &'static str {
+        "3D Medusa"
+    }
 
-    let mut placed = vec![];
-    for cell in &possible[0] {
-        if possible[1..].iter().all(|placement| placement.contains(cell)) {
-            placed.push(*cell);
-        }
+    fn difficulty(&self) -> f32 {
+        7.5
     }
 
-    let eliminated: Vec<_> = cells
-        .iter()
-        .flat_map(|c| (0..9).filter(|v| is_set!(board[*c], v)).map(move |v| (*c, v)))
-        .filter(|(c, v)| {
-            possible
-                .iter()
-                .all(|possibility| possibility.iter().any(|(other_c, other_v)| c != other_c && v == other_v && c.can_see(board, other_c)))
-        })
-        .collect();
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board, true).iter().map(ColourMap::deduction).collect()
+    }
+}
 
-    ColourMap { eliminated, placed }
+/// Colours each cluster and applies the colouring rules to it. Simple colouring links a single digit through conjugate
+/// pairs, while 3D Medusa also links the two digits of bivalue cells and so can use every rule.
+pub fn from_board(board: &Board, medusa: bool) -> Rc<[ColourMap]> {
+    clusters(board, medusa).iter().flat_map(|cluster| cluster.colour_maps(board, medusa)).collect()
 }
 
-impl Colouring<'_> {
-    fn new(board: &Board) -> Colouring<'_> {
-        Colouring { nodes: vec![], board }
+pub fn clusters(board: &Board, medusa: bool) -> Vec<Cluster> {
+    #[allow(clippy::cast_possible_truncation)]
+    let mut links: Vec<_> = (1..=board.size)
+        .map(|val| val as u32)
+        .flat_map(|val| conjugate_pairs(board, val).into_iter().map(move |(a, b)| ((a, val), (b, val))))
+        .collect();
+    if medusa {
+        links.extend(bivalue_cells(board).into_iter().map(|cell| {
+            let val = board[cell].trailing_zeros();
+            ((cell, val), (cell, (board[cell] & !(1 << val)).trailing_zeros()))
+        }));
     }
 
-    fn add_cell(&mut self, cell: Cell, possible: u16) {
-        let vals = get_possible_pair(possible);
-        self.nodes.push(ColourNode {
-            cell,
-            val: vals.0,
-            state: State::None,
-            connects_to: vec![],
-        });
-        self.nodes.push(ColourNode {
-            cell,
-            val: vals.1,
-            state: State::None,
-            connects_to: vec![],
-        });
-    }
+    let mut coloured: Vec<(Cell, u32)> = vec![];
+    let mut clusters = vec![];
+    for (start, _) in &links {
+        if coloured.contains(start) {
+            continue;
+        }
 
-    fn add_pair(&mut self, cell_a: Cell, cell_b: Cell, val: u16) {
-        let len = self.nodes.len();
+        let mut nodes = vec![(start.0, start.1, true)];
+        let mut i = 0;
+        while let Some((cell, val, colour)) = nodes.get(i).copied() {
+            for (a, b) in &links {
+                let next = if *a == (cell, val) {
+                    b
+                } else if *b == (cell, val) {
+                    a
+                } else {
+                    continue;
+                };
+                if !nodes.iter().any(|(c, v, _)| (*c, *v) == *next) {
+                    nodes.push((next.0, next.1, !colour));
+                }
+            }
+            i += 1;
+        }
 
-        self.nodes.push(ColourNode {
-            cell: cell_a,
-            val,
-            state: State::None,
-            connects_to: vec![(len + 1, true)],
-        });
+        coloured.extend(nodes.iter().map(|(cell, val, _)| (*cell, *val)));
+        clusters.push(Cluster { nodes });
+    }
 
-        self.nodes.push(ColourNode {
-            cell: cell_b,
-            val,
-            state: State::None,
-            connects_to: vec![(len, true)],
-        });
+    clusters
+}
+
+impl Cluster {
+    fn colour_of(&self, cell: Cell, val: u32) -> Option<bool> {
+        self.nodes.iter().find(|(c, v, _)| *c == cell && *v == val).map(|(_, _, colour)| *colour)
     }
 
-    fn dedup(&mut self) {
-        let mut new: Vec<(Vec<usize>, ColourNode)> = vec![];
+    /// Whether `cell` sees a different cell holding `val` in `colour`.
+    fn sees_colour(&self, board: &Board, cell: Cell, val: u32, colour: bool) -> bool {
+        self.nodes.iter().any(|(c, v, col)| *c != cell && *v == val && *col == colour && c.can_see(board, &cell))
+    }
 
-        for i in 0..self.nodes.len() {
-            let node = self.nodes.get_mut(i).unwrap();
-            if let Some(same_i) = new.iter().position(|(_, n)| n.cell == node.cell && n.val == node.val) {
-                let same = new.get_mut(same_i).unwrap();
+    fn colour_maps(&self, board: &Board, medusa: bool) -> Vec<ColourMap> {
+        let mut cells: Vec<_> = self.nodes.iter().map(|(cell, _, _)| *cell).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        let digits = self.nodes.iter().fold(0, |acc, (_, val, _)| acc | 1 << val);
+        let map = |rule, eliminated, placed| ColourMap {
+            rule,
+            cells: cells.clone(),
+            digits,
+            eliminated,
+            placed,
+        };
 
-                same.1.connects_to.append(&mut node.connects_to);
-                same.0.push(i);
-            } else {
-                new.push((vec![i], node.clone()));
+        // A colour which leads to a contradiction is false, so every candidate of the other colour is true
+        for colour in [true, false] {
+            if let Some(rule) = self.find_contradiction(board, colour, medusa) {
+                let of_colour = |wanted| self.nodes.iter().filter(move |(_, _, col)| *col == wanted).map(|(cell, val, _)| (*cell, *val)).collect();
+                return vec![map(rule, of_colour(colour), of_colour(!colour))];
             }
         }
 
-        self.nodes = vec![];
-        for (_, node) in &new {
-            let new_connections: Vec<_> = node
-                .connects_to
+        let candidates: Vec<_> = board_candidates(board).into_iter().filter(|(cell, val)| self.colour_of(*cell, *val).is_none()).collect();
+        let mut maps = vec![];
+
+        let eliminated: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|(cell, val)| self.sees_colour(board, *cell, *val, true) && self.sees_colour(board, *cell, *val, false))
+            .collect();
+        if !eliminated.is_empty() {
+            maps.push(map(Rule::TwoColoursElsewhere, eliminated, vec![]));
+        }
+
+        if medusa {
+            let eliminated: Vec<_> = candidates
                 .iter()
-                .map(|(i, connection)| (new.iter().position(|(old_i, _)| old_i.contains(i)).unwrap(), *connection))
+                .copied()
+                .filter(|(cell, _)| [true, false].iter().all(|colour| self.nodes.iter().any(|(c, _, col)| c == cell && col == colour)))
                 .collect();
+            if !eliminated.is_empty() {
+                maps.push(map(Rule::TwoColoursInCell, eliminated, vec![]));
+            }
 
-            self.nodes.push(ColourNode {
-                cell: node.cell,
-                val: node.val,
-                state: node.state,
-                connects_to: new_connections,
-            });
+            let eliminated: Vec<_> = candidates
+                .iter()
+                .copied()
+                .filter(|(cell, val)| self.nodes.iter().filter(|(c, _, _)| c == cell).any(|(_, _, colour)| self.sees_colour(board, *cell, *val, !colour)))
+                .collect();
+            if !eliminated.is_empty() {
+                maps.push(map(Rule::TwoColoursUnitAndCell, eliminated, vec![]));
+            }
         }
+
+        maps
     }
 
-    fn connect(&mut self) {
-        let pairs: Vec<_> = (0..self.nodes.len()).zip(1..).flat_map(|(a, i)| (i..self.nodes.len()).map(move |b| (a, b))).collect();
-        for (i1, i2) in pairs {
-            if let Some((n1, n2)) = get_mut_pair(&mut self.nodes, (i1, i2)) {
-                if (n1.cell == n2.cell) || (n1.val == n2.val && n1.cell.can_see(self.board, &n2.cell)) {
-                    n1.connects_to.push((i2, false));
-                    n2.connects_to.push((i1, false));
-                }
-            }
+    fn find_contradiction(&self, board: &Board, colour: bool, medusa: bool) -> Option<Rule> {
+        let nodes: Vec<_> = self.nodes.iter().filter(|(_, _, col)| *col == colour).collect();
+
+        if medusa && nodes.iter().zip(1..).any(|((a, _, _), i)| nodes[i..].iter().any(|(b, _, _)| a == b)) {
+            return Some(Rule::TwiceInCell);
         }
-    }
 
-    fn colour(&mut self, node: usize, state: State) -> bool {
-        if state == State::None {
-            return true;
+        let twice_in_unit = units(board).iter().any(|unit| {
+            let unit_cells = unit.cells(board.size);
+            let in_unit: Vec<_> = nodes.iter().filter(|(cell, _, _)| unit_cells.contains(cell)).collect();
+            in_unit.iter().zip(1..).any(|((_, a, _), i)| in_unit[i..].iter().any(|(_, b, _)| a == b))
+        });
+        if twice_in_unit {
+            return Some(Rule::TwiceInUnit);
         }
-        let cascade = if let Some(node) = self.nodes.get_mut(node) {
-            if node.state == State::None {
-                node.state = state;
-                node.connects_to.clone()
-            } else {
-                return node.state == state;
-            }
-        } else {
-            return false;
+
+        let emptied = |cell: &Cell| {
+            let vals = board[*cell];
+            let mut cell_vals = digits(vals);
+            vals.count_ones() > 1 && self.nodes.iter().all(|(c, _, _)| c != cell) && cell_vals.all(|val| self.sees_colour(board, *cell, val, colour))
         };
+        if medusa && cells(board).iter().any(emptied) {
+            return Some(Rule::CellEmptiedByColour);
+        }
 
-        cascade.iter().all(|(other, is_pair)| {
-            if state == State::True {
-                self.colour(*other, State::False)
-            } else if *is_pair && state == State::False {
-                self.colour(*other, State::True)
-            } else {
-                true
-            }
-        })
+        None
     }
+}
 
-    fn get_possible_colourings(&self) -> Vec<Vec<(Cell, u16)>> {
-        let mut out = vec![];
+fn board_candidates(board: &Board) -> Vec<(Cell, u32)> {
+    cells(board)
+        .iter()
+        .filter(|cell| board[**cell].count_ones() > 1)
+        .flat_map(|cell| digits(board[*cell]).map(move |val| (*cell, val)))
+        .collect()
+}
 
-        if let Some(first) = self.nodes.iter().position(|node| node.state == State::None) {
-            let mut tmp;
+impl Rule {
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::TwiceInCell => "colour twice in a cell",
+            Rule::TwiceInUnit => "colour twice in a unit",
+            Rule::TwoColoursInCell => "two colours in a cell",
+            Rule::TwoColoursElsewhere => "two colours elsewhere",
+            Rule::TwoColoursUnitAndCell => "two colours in a unit and cell",
+            Rule::CellEmptiedByColour => "cell emptied by colour",
+        }
+    }
+}
 
-            tmp = self.clone();
-            if tmp.colour(first, State::True) {
-                out.append(&mut tmp.get_possible_colourings());
-            }
+impl ColourMap {
+    pub fn deduction(&self) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.clone(), self.digits);
+        deduction.rule = Some(self.rule.description());
+        deduction.placed.clone_from(&self.placed);
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
 
-            tmp = self.clone();
-            if tmp.colour(first, State::False) {
-                out.append(&mut tmp.get_possible_colourings());
-            }
-        } else {
-            out.push(self.placed_digits());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(row: usize, col: usize) -> Cell {
+        Cell { row, col }
+    }
+
+    /// Removes `val` from every cell of `cells` except those in `keep`.
+    fn restrict(board: &mut Board, cells: impl Iterator<Item = Cell>, val: u32, keep: &[Cell]) {
+        for cell in cells.filter(|cell| !keep.contains(cell)) {
+            board[cell] &= !(1 << val);
         }
+    }
 
-        out
+    fn find_rule(board: &Board, medusa: bool, rule: Rule) -> ColourMap {
+        let maps: Vec<_> = from_board(board, medusa).iter().filter(|map| map.rule == rule).cloned().collect();
+        assert_eq!(maps.len(), 1);
+        let mut map = maps[0].clone();
+        map.placed.sort_unstable();
+        map.eliminated.sort_unstable();
+        map
     }
 
-    fn placed_digits(&self) -> Vec<(Cell, u16)> {
-        #[allow(clippy::cast_possible_truncation)]
-        self.nodes.iter().filter(|n| n.state == State::True).map(|n| (n.cell, n.val)).collect()
+    #[test]
+    fn simple_colouring_eliminates_what_sees_both_colours() {
+        // 1 is coloured r1c1 = r1c5 - r6c5 = r6c1, so r1c1 and r6c1 take opposite colours down the first column
+        let mut board = Board::new(9);
+        restrict(&mut board, (0..9).map(|col| cell(0, col)), 1, &[cell(0, 0), cell(0, 4)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 4)), 1, &[cell(0, 4), cell(5, 4)]);
+        restrict(&mut board, (0..9).map(|col| cell(5, col)), 1, &[cell(5, 4), cell(5, 0)]);
+
+        let map = find_rule(&board, false, Rule::TwoColoursElsewhere);
+        let expected: Vec<_> = [1, 2, 3, 4, 6, 7, 8].map(|row| (cell(row, 0), 1)).into();
+        assert_eq!(map.eliminated, expected);
+        assert!(map.placed.is_empty());
     }
-}
 
-#[allow(clippy::cast_possible_truncation)]
-fn get_possible_pair(val: u16) -> (u16, u16) {
-    let val1 = val.trailing_zeros() as u16;
-    (val1, (val >> (val1 + 1)).trailing_zeros() as u16 + val1 + 1)
-}
+    #[test]
+    fn medusa_colour_twice_in_a_cell_is_false() {
+        // r1c1 {1,2} and r5c1 {2,3} join 1 in the first row, 2 in the first column and 3 in the fifth row and column,
+        // which colour both 1 and 3 in r1c5 alike
+        let mut board = Board::new(9);
+        board[cell(0, 0)] = 1 << 1 | 1 << 2;
+        board[cell(4, 0)] = 1 << 2 | 1 << 3;
+        restrict(&mut board, (0..9).map(|col| cell(0, col)), 1, &[cell(0, 0), cell(0, 4)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 0)), 2, &[cell(0, 0), cell(4, 0)]);
+        restrict(&mut board, (0..9).map(|col| cell(4, col)), 3, &[cell(4, 0), cell(4, 4)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 4)), 3, &[cell(4, 4), cell(0, 4)]);
+
+        let map = find_rule(&board, true, Rule::TwiceInCell);
+        assert_eq!(map.placed, [(cell(0, 0), 1), (cell(4, 0), 2), (cell(4, 4), 3)]);
+        assert_eq!(map.eliminated, [(cell(0, 0), 2), (cell(0, 4), 1), (cell(0, 4), 3), (cell(4, 0), 3)]);
+    }
 
-fn get_mut_pair<T>(slice: &mut [T], index: (usize, usize)) -> Option<(&mut T, &mut T)> {
-    if index.0 == index.1 {
-        None
-    } else {
-        let (first, second) = (min(index.0, index.1), max(index.0, index.1));
-        let (_, tmp) = slice.split_at_mut(first);
-        let (x, rest) = tmp.split_at_mut(1);
-        let (_, y) = rest.split_at_mut(second - first - 1);
-        Some(if index.0 < index.1 { (&mut x[0], &mut y[0]) } else { (&mut y[0], &mut x[0]) })
+    #[test]
+    fn medusa_colour_twice_in_a_unit_is_false() {
+        // 1 is coloured r1c1 = r1c5 - r5c5 = r5c2 - r2c2, putting r1c1 and r2c2 in the top left box alike
+        let mut board = Board::new(9);
+        restrict(&mut board, (0..9).map(|col| cell(0, col)), 1, &[cell(0, 0), cell(0, 4)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 4)), 1, &[cell(0, 4), cell(4, 4)]);
+        restrict(&mut board, (0..9).map(|col| cell(4, col)), 1, &[cell(4, 4), cell(4, 1)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 1)), 1, &[cell(4, 1), cell(1, 1)]);
+
+        let map = find_rule(&board, true, Rule::TwiceInUnit);
+        assert_eq!(map.placed, [(cell(0, 4), 1), (cell(4, 1), 1)]);
+        assert_eq!(map.eliminated, [(cell(0, 0), 1), (cell(1, 1), 1), (cell(4, 4), 1)]);
+    }
+
+    #[test]
+    fn medusa_colour_emptying_a_cell_is_false() {
+        // r1c1 {1,2} and r1c6 {2,3} join 2 in the first row and 3 in the sixth column, colouring 1 in r1c1 and 3 in
+        // r2c6 alike, which between them empty r2c2 {1,3}
+        let mut board = Board::new(9);
+        board[cell(0, 0)] = 1 << 1 | 1 << 2;
+        board[cell(0, 5)] = 1 << 2 | 1 << 3;
+        board[cell(1, 1)] = 1 << 1 | 1 << 3;
+        restrict(&mut board, (0..9).map(|col| cell(0, col)), 2, &[cell(0, 0), cell(0, 5)]);
+        restrict(&mut board, (0..9).map(|row| cell(row, 5)), 3, &[cell(0, 5), cell(1, 5)]);
+
+        let map = find_rule(&board, true, Rule::CellEmptiedByColour);
+        assert_eq!(map.placed, [(cell(0, 0), 2), (cell(0, 5), 3)]);
+        assert_eq!(map.eliminated, [(cell(0, 0), 1), (cell(0, 5), 2), (cell(1, 5), 3)]);
     }
 }
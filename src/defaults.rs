@@ -1,25 +1,14 @@
-use crate::{
-    board::{Cell, Region},
-    SIZE,
-};
+use crate::board::{Cell, Region};
 
-pub const fn default_cell() -> u16 {
-    match SIZE {
-        4 => 0b1_1110,
-        6 => 0b111_1110,
-        9 => 0b11_1111_1110,
-        _ => panic!("Default cell with specified size does not exist"),
-    }
+pub const fn default_cell(size: usize) -> u32 {
+    #[allow(clippy::cast_possible_truncation)]
+    let size = size as u32;
+    ((1 << size) - 1) << 1
 }
 
-pub fn default_regions() -> Vec<Region> {
-    let out: Vec<Region> = match SIZE {
-        4 => calc_region(2, 2),
-        6 => calc_region(3, 2),
-        9 => calc_region(3, 3),
-        _ => panic!("Default regions for specified size does not exist"),
-    };
-    out
+pub fn default_regions(size: usize) -> Vec<Region> {
+    let (width, height) = default_region_bounds(size).expect("Default regions for specified size does not exist");
+    calc_region(width, height)
 }
 
 fn calc_region(width: usize, height: usize) -> Vec<Region> {
@@ -34,11 +23,15 @@ fn calc_region_contents(width: usize, height: usize, offset_x: usize, offset_y:
     (0..width).flat_map(|x| (0..height).map(move |y| Cell { col: x + offset_x, row: y + offset_y })).collect()
 }
 
-pub fn default_region_bounds() -> (usize, usize) {
-    match SIZE {
-        4 => (2, 2),
-        6 => (3, 2),
-        9 => (3, 3),
-        _ => panic!("Default regions for specified size does not exist"),
+pub fn default_region_bounds(size: usize) -> Option<(usize, usize)> {
+    match size {
+        4 => Some((2, 2)),
+        6 => Some((3, 2)),
+        8 => Some((4, 2)),
+        9 => Some((3, 3)),
+        12 => Some((4, 3)),
+        16 => Some((4, 4)),
+        25 => Some((5, 5)),
+        _ => None,
     }
 }
@@ -0,0 +1,26 @@
+use std::{error::Error, fmt::Display};
+
+use crate::board::Cell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    Contradiction { cell: Cell, reason: &'static str },
+    InvalidGiven { cell: Cell, val: u32 },
+    NoCandidates { cell: Cell },
+    NoSolution,
+    UnsupportedSize { size: usize },
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Contradiction { cell, reason } => write!(f, "Contradiction in {cell}: {reason}"),
+            SolveError::InvalidGiven { cell, val } => write!(f, "Given {val} in {cell} conflicts with another given"),
+            SolveError::NoCandidates { cell } => write!(f, "Cell {cell} has no possibilities"),
+            SolveError::NoSolution => write!(f, "Search exhausted every candidate"),
+            SolveError::UnsupportedSize { size } => write!(f, "Grids of size {size} are not supported"),
+        }
+    }
+}
+
+impl Error for SolveError {}
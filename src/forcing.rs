@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    defaults::default_cell,
+    error::SolveError,
+    misc::{cells, digits, is_set, units},
+    search,
+    solver::{Solver, Technique},
+    step::Deduction,
+};
+
+pub struct Nishio;
+
+pub struct CellForcingChains;
+
+pub struct UnitForcingChains;
+
+pub struct DigitForcingChains;
+
+/// What holds on every branch of an assumption. A branch which runs into a contradiction is dropped, as the assumption
+/// behind it must be false.
+#[derive(Debug, Clone)]
+pub struct Forcing {
+    /// The candidates assumed, one per branch, of which one must be true. Digit forcing chains assume a single candidate
+    /// both true and false.
+    pub branches: Vec<(Cell, u32)>,
+    pub placed: Vec<(Cell, u32)>,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+macro_rules! impl_forcing {
+    ($technique:ident, $name:literal, $difficulty:literal, $from_board:ident) => {
+        impl Technique for $technique {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                $from_board(board).iter().map(Forcing::deduction).collect()
+            }
+        }
+    };
+}
+
+impl_forcing!(Nishio, "Nishio", 7.6, from_board_nishio);
+impl_forcing!(CellForcingChains, "Cell Forcing Chain", 8.3, from_board_cell);
+impl_forcing!(UnitForcingChains, "Unit Forcing Chain", 8.4, from_board_unit);
+impl_forcing!(DigitForcingChains, "Digit Forcing Chain", 8.6, from_board_digit);
+
+/// Places each candidate in turn, eliminating those which lead to a contradiction.
+pub fn from_board_nishio(board: &Board) -> Rc<[Forcing]> {
+    let basic = Solver::basic();
+    candidates(board)
+        .into_iter()
+        .filter(|(cell, val)| branch(board, &basic, |branch| branch.place_digit(*val, *cell)).is_none())
+        .map(|candidate| Forcing {
+            branches: vec![candidate],
+            placed: vec![],
+            eliminated: vec![candidate],
+        })
+        .collect()
+}
+
+/// Places each candidate of an unsolved cell in turn, as one of them must be true.
+pub fn from_board_cell(board: &Board) -> Rc<[Forcing]> {
+    let basic = Solver::basic();
+    cells(board)
+        .iter()
+        .filter(|cell| board[**cell].count_ones() > 1)
+        .filter_map(|cell| {
+            let branches: Vec<_> = digits(board[*cell]).map(|val| (*cell, val)).collect();
+            let boards = branches.iter().map(|(cell, val)| branch(board, &basic, |branch| branch.place_digit(*val, *cell))).collect();
+            forcing(board, branches, boards)
+        })
+        .collect()
+}
+
+/// Places a digit in each of its places in a unit in turn, as it must go in one of them.
+pub fn from_board_unit(board: &Board) -> Rc<[Forcing]> {
+    let basic = Solver::basic();
+    let mut out = vec![];
+
+    for unit in units(board).iter() {
+        let unit_cells = unit.cells(board.size);
+        for val in digits(default_cell(board.size)) {
+            let places: Vec<_> = unit_cells.iter().copied().filter(|cell| is_set!(board[*cell], val)).collect();
+            if places.len() < 2 {
+                continue;
+            }
+
+            let branches: Vec<_> = places.iter().map(|cell| (*cell, val)).collect();
+            let boards = branches.iter().map(|(cell, val)| branch(board, &basic, |branch| branch.place_digit(*val, *cell))).collect();
+            out.extend(forcing(board, branches, boards));
+        }
+    }
+
+    out.into()
+}
+
+/// Assumes each candidate both true and false.
+pub fn from_board_digit(board: &Board) -> Rc<[Forcing]> {
+    let basic = Solver::basic();
+    candidates(board)
+        .into_iter()
+        .filter_map(|(cell, val)| {
+            let boards = vec![
+                branch(board, &basic, |branch| branch.place_digit(val, cell)),
+                branch(board, &basic, |branch| branch.clean_cell(cell, val).map(|_| ())),
+            ];
+            forcing(board, vec![(cell, val)], boards)
+        })
+        .collect()
+}
+
+/// Applies an assumption to a copy of the board and propagates it with the basic techniques, giving nothing if that
+/// leads to a contradiction.
+fn branch(board: &Board, basic: &Solver, assume: impl FnOnce(&mut Board) -> Result<(), SolveError>) -> Option<Board> {
+    let mut branch = Board {
+        regions: board.regions.clone(),
+        cells: board.cells.clone(),
+        givens: board.givens.clone(),
+        log: vec![],
+        ..*board
+    };
+    assume(&mut branch).and_then(|()| search::propagate(&mut branch, basic)).ok().map(|()| branch)
+}
+
+/// Collects what every surviving branch agrees on.
+fn forcing(board: &Board, branches: Vec<(Cell, u32)>, boards: Vec<Option<Board>>) -> Option<Forcing> {
+    let boards: Vec<_> = boards.into_iter().flatten().collect();
+    if boards.is_empty() {
+        return None;
+    }
+
+    let mut placed = vec![];
+    let mut eliminated = vec![];
+    for cell in cells(board).iter().filter(|cell| board[**cell].count_ones() > 1) {
+        let union = boards.iter().fold(0, |acc, branch| acc | branch[*cell]);
+        if union.count_ones() == 1 {
+            placed.push((*cell, union.trailing_zeros()));
+        } else {
+            eliminated.extend(digits(board[*cell] & !union).map(|val| (*cell, val)));
+        }
+    }
+
+    (!placed.is_empty() || !eliminated.is_empty()).then_some(Forcing { branches, placed, eliminated })
+}
+
+fn candidates(board: &Board) -> Vec<(Cell, u32)> {
+    cells(board)
+        .iter()
+        .filter(|cell| board[**cell].count_ones() > 1)
+        .flat_map(|cell| digits(board[*cell]).map(|val| (*cell, val)))
+        .collect()
+}
+
+impl Forcing {
+    pub fn deduction(&self) -> Deduction {
+        let mut cells: Vec<_> = self.branches.iter().map(|(cell, _)| *cell).collect();
+        cells.dedup();
+        let digits = self.branches.iter().fold(0, |acc, (_, val)| acc | 1 << val);
+
+        let mut deduction = Deduction::new(cells, digits);
+        deduction.placed.clone_from(&self.placed);
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut eliminated: Vec<(Cell, u32)>) -> Vec<(Cell, u32)> {
+        eliminated.sort_unstable();
+        eliminated.dedup();
+        eliminated
+    }
+
+    #[test]
+    fn nishio_eliminates_what_empties_a_pair() {
+        // A naked pair of 1 and 2 in the top left box and first row
+        let mut board = Board::new(4);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2;
+        board[Cell { row: 0, col: 1 }] = 1 << 1 | 1 << 2;
+
+        let eliminated = sorted(from_board_nishio(&board).iter().flat_map(|forcing| forcing.eliminated.clone()).collect());
+        // The pair's peers lose 1 and 2, leaving 3 and 4 as a pair in the rest of the box to be taken from the second row
+        let peers = [(0, 2), (0, 3), (1, 0), (1, 1)].map(|(row, col)| Cell { row, col });
+        let rest = [(1, 2), (1, 3)].map(|(row, col)| Cell { row, col });
+        let expected = sorted(
+            peers
+                .into_iter()
+                .flat_map(|cell| [(cell, 1), (cell, 2)])
+                .chain(rest.into_iter().flat_map(|cell| [(cell, 3), (cell, 4)]))
+                .collect(),
+        );
+        assert_eq!(eliminated, expected);
+    }
+
+    #[test]
+    fn cell_forcing_chain_agrees_across_branches() {
+        // Whichever of 1 or 2 goes in r1c1, r1c2 and r2c1 take the other two of 1, 2 and 3
+        let mut board = Board::new(4);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2;
+        board[Cell { row: 0, col: 1 }] = 1 << 1 | 1 << 3;
+        board[Cell { row: 1, col: 0 }] = 1 << 2 | 1 << 3;
+
+        let forcings = from_board_cell(&board);
+        let forcing = forcings.iter().find(|forcing| forcing.branches[0].0 == Cell { row: 0, col: 0 }).unwrap();
+        assert_eq!(forcing.branches, [(Cell { row: 0, col: 0 }, 1), (Cell { row: 0, col: 0 }, 2)]);
+        // The box is left needing 4 in its last cell either way
+        assert!(forcing.placed.contains(&(Cell { row: 1, col: 1 }, 4)));
+        // Only 1 is taken from the rest of the first row on both branches
+        assert!(forcing.eliminated.contains(&(Cell { row: 0, col: 3 }, 1)));
+        assert!(!forcing.eliminated.contains(&(Cell { row: 0, col: 3 }, 2)));
+        assert!(!forcing.eliminated.contains(&(Cell { row: 0, col: 3 }, 3)));
+    }
+}
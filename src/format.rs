@@ -1,11 +1,11 @@
-use crate::{board::Board, defaults::default_region_bounds};
+use crate::board::{Board, Givens};
 
 pub fn format(board: &Board) -> Option<String> {
     if board.is_solved() {
         return format_solved(board);
     }
 
-    let (rwidth, rheight) = default_region_bounds();
+    let (rwidth, rheight) = (board.box_width, board.box_height);
     let mut out = String::new();
     out.push('╔');
     for i in 0..rheight {
@@ -67,7 +67,7 @@ pub fn format(board: &Board) -> Option<String> {
                             out.push(
                                 if board.get_cell_coords(cell_row + region_row * rheight, cell_col + region_col * rwidth)? & 1 << (digit_col + digit_row * rwidth) > 0 {
                                     #[allow(clippy::cast_possible_truncation)]
-                                    char::from_digit((digit_col + digit_row * rwidth) as u32, 10).unwrap_or(' ')
+                                    digit_char((digit_col + digit_row * rwidth) as u32, board.size)
                                 } else {
                                     ' '
                                 },
@@ -98,7 +98,7 @@ pub fn format(board: &Board) -> Option<String> {
 }
 
 fn format_solved(board: &Board) -> Option<String> {
-    let (rwidth, rheight) = default_region_bounds();
+    let (rwidth, rheight) = (board.box_width, board.box_height);
     let mut out = String::new();
     out.push('╔');
     for i in 0..rheight {
@@ -109,10 +109,10 @@ fn format_solved(board: &Board) -> Option<String> {
     }
     out.push('╗');
 
-    for region_row in 0..rheight {
+    for region_row in 0..rwidth {
         if region_row > 0 {
             out.push_str("\n╟");
-            for i in 0..rwidth {
+            for i in 0..rheight {
                 if i > 0 {
                     out.push('┼');
                 }
@@ -124,12 +124,12 @@ fn format_solved(board: &Board) -> Option<String> {
         for cell_row in 0..rheight {
             out.push_str("\n║");
 
-            for region_col in 0..rwidth {
+            for region_col in 0..rheight {
                 if region_col > 0 {
                     out.push('│');
                 }
                 for cell_col in 0..rwidth {
-                    out.push(char::from_digit(board.get_cell_coords(cell_row + region_row * rheight, cell_col + region_col * rwidth)?.trailing_zeros(), 10).unwrap_or(' '));
+                    out.push(digit_char(board.get_cell_coords(cell_row + region_row * rheight, cell_col + region_col * rwidth)?.trailing_zeros(), board.size));
                 }
             }
 
@@ -148,3 +148,99 @@ fn format_solved(board: &Board) -> Option<String> {
 
     Some(out)
 }
+
+/// Writes givens in the format accepted by `parse_givens`.
+pub fn format_givens(givens: &Givens) -> String {
+    givens
+        .iter()
+        .map(|row| row.iter().map(|val| if *val == 0 { '-' } else { digit_char(*val, row.len()) }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads every puzzle in `data`, separated by blank lines and ending at an optional `END` marker.
+pub fn parse_puzzles(data: &str, zero_based: bool) -> Vec<Givens> {
+    let data = if let Some(data) = data.split_once("END") { data.0 } else { data };
+
+    data.trim().split("\n\n").map(|puzzle| parse_givens(puzzle, zero_based)).collect()
+}
+
+/// Reads one puzzle, a line per row, in which any character that is not a digit of the grid marks an empty cell.
+///
+/// Grids up to 16x16 use `1-9` followed by letters, so 16x16 is written `1-G`, unless `zero_based` is set, in which case
+/// 16x16 is written `0-F`. 25x25 grids use the letters `A-Y`.
+pub fn parse_givens(puzzle: &str, zero_based: bool) -> Givens {
+    let size = puzzle.lines().count();
+    let zero_based = zero_based && size == 16;
+
+    puzzle
+        .lines()
+        .map(|line| {
+            line.trim_end()
+                .chars()
+                .map(|chr| {
+                    let val = if size > 16 {
+                        chr.to_ascii_uppercase().is_ascii_uppercase().then(|| u32::from(chr.to_ascii_uppercase()) - u32::from('A') + 1)
+                    } else if zero_based {
+                        chr.to_digit(16).map(|d| d + 1)
+                    } else {
+                        chr.to_digit(36)
+                    };
+                    val.filter(|val| (1..=size).contains(&(*val as usize))).unwrap_or(0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn digit_char(val: u32, size: usize) -> char {
+    if size > 16 {
+        char::from_u32(u32::from('A') + val - 1).unwrap_or(' ')
+    } else {
+        char::from_digit(val, 36).map_or(' ', |chr| chr.to_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solved grid of `size` with boxes of `width` columns, with the last cell of each row left empty.
+    fn pattern(size: usize, width: usize) -> Givens {
+        #[allow(clippy::cast_possible_truncation)]
+        (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|col| if col == size - 1 { 0 } else { ((row % width * width + row / width + col) % size + 1) as u32 })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sixteen_by_sixteen_reads_both_notations() {
+        let givens = pattern(16, 4);
+        let one_based = format_givens(&givens);
+        let zero_based: String = one_based
+            .chars()
+            .map(|chr| chr.to_digit(17).and_then(|val| char::from_digit(val - 1, 16)).map_or(chr, |chr| chr.to_ascii_uppercase()))
+            .collect();
+        assert!(one_based.contains('G') && !one_based.contains('0'));
+        assert!(zero_based.contains('0') && !zero_based.contains('G'));
+
+        assert_eq!(parse_givens(&one_based, false), givens);
+        assert_eq!(parse_givens(&zero_based, true), givens);
+        assert_eq!(parse_givens(&one_based.replace('-', "0"), false), givens);
+    }
+
+    #[test]
+    fn twenty_five_by_twenty_five_uses_letters() {
+        let givens = pattern(25, 5);
+        let text = format_givens(&givens);
+        assert!(text.starts_with("ABCDEFGHIJKLMNOPQRSTUVWX-\nFGHIJ"));
+        assert!(text.contains('Y') && !text.contains('Z'));
+
+        assert_eq!(parse_givens(&text, false), givens);
+        assert_eq!(parse_givens(&text.to_ascii_lowercase(), false), givens);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    board::{Board, Cell, Givens},
+    defaults::default_region_bounds,
+    error::SolveError,
+    misc::cells,
+    rating, search,
+};
+
+/// Branches the uniqueness check may search before a removal is abandoned. Only sparse 25x25 grids come near it.
+const COUNT_BUDGET: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+    Rotational,
+    Mirror,
+}
+
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: Rng,
+    size: usize,
+    symmetry: Symmetry,
+}
+
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Generator {
+    pub fn new(size: usize, seed: u64, symmetry: Symmetry) -> Result<Self, SolveError> {
+        if default_region_bounds(size).is_none() {
+            return Err(SolveError::UnsupportedSize { size });
+        }
+        Ok(Generator { rng: Rng(seed), size, symmetry })
+    }
+
+    /// Generates a puzzle with a unique solution from which no given, or symmetric pair of givens, can be removed.
+    /// Givens whose removal cannot be settled within `COUNT_BUDGET` branches are kept, so the largest grids may not be minimal.
+    pub fn generate(&mut self) -> Givens {
+        let solution = self.fill();
+        let mut givens: Givens = solution.cells.iter().map(|row| row.iter().map(|val| val.trailing_zeros()).collect()).collect();
+
+        let mut order: Vec<_> = cells(&solution).to_vec();
+        self.rng.shuffle(&mut order);
+
+        for cell in order {
+            if givens[cell.row][cell.col] == 0 {
+                continue;
+            }
+
+            let mut attempt = givens.clone();
+            for removed in [cell, self.symmetry.partner(cell, self.size)] {
+                attempt[removed.row][removed.col] = 0;
+            }
+            let mut budget = COUNT_BUDGET;
+            if Board::from_givens(&attempt).is_ok_and(|board| search::count_within(&board, 2, &mut budget) == Some(1)) {
+                givens = attempt;
+            }
+        }
+
+        givens
+    }
+
+    /// Generates puzzles until one whose rating lies within `min..=max` is found, giving up after `attempts` puzzles.
+    /// Generated puzzles have a single solution, so they are rated with the uniqueness techniques enabled.
+    pub fn generate_rated(&mut self, min: f32, max: f32, attempts: usize) -> Option<Givens> {
+        (0..attempts).map(|_| self.generate()).find(|givens| {
+            let Ok(mut board) = Board::from_givens(givens) else {
+                return false;
+            };
+            board.unique = true;
+            board.solve().is_ok() && board.is_solved() && (min..=max).contains(&rating::from_log(&board.log).score)
+        })
+    }
+
+    /// Solves the empty grid by search and shuffles the solution. Relabelling the digits and reordering bands of boxes,
+    /// or the lines within a band, all keep the grid valid.
+    fn fill(&mut self) -> Board {
+        let mut board = search::search(&Board::new(self.size)).expect("An empty board always has a solution");
+        let (width, height) = (board.box_width, board.box_height);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut vals: Vec<_> = (1..=self.size as u32).collect();
+        self.rng.shuffle(&mut vals);
+        let rows = self.lines(height, self.size / height);
+        let cols = self.lines(width, self.size / width);
+
+        board.cells = rows
+            .iter()
+            .map(|row| cols.iter().map(|col| 1 << vals[board.cells[*row][*col].trailing_zeros() as usize - 1]).collect())
+            .collect();
+        board
+    }
+
+    /// The lines of `count` bands of `width` lines each, with both the bands and the lines within them shuffled.
+    fn lines(&mut self, width: usize, count: usize) -> Vec<usize> {
+        let mut bands: Vec<_> = (0..count).collect();
+        self.rng.shuffle(&mut bands);
+        bands
+            .into_iter()
+            .flat_map(|band| {
+                let mut lines: Vec<_> = (band * width..(band + 1) * width).collect();
+                self.rng.shuffle(&mut lines);
+                lines
+            })
+            .collect()
+    }
+}
+
+impl Symmetry {
+    fn partner(self, cell: Cell, size: usize) -> Cell {
+        match self {
+            Symmetry::None => cell,
+            Symmetry::Rotational => Cell {
+                row: size - 1 - cell.row,
+                col: size - 1 - cell.col,
+            },
+            Symmetry::Mirror => Cell {
+                row: cell.row,
+                col: size - 1 - cell.col,
+            },
+        }
+    }
+}
+
+impl Rng {
+    // splitmix64, which is well distributed for any seed including 0
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            #[allow(clippy::cast_possible_truncation)]
+            slice.swap(i, (self.next() % (i as u64 + 1)) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+
+    #[test]
+    fn generated_puzzles_are_rated_as_the_cli_rates_them() {
+        // Without the uniqueness techniques this puzzle rates 6.2 rather than 4.6.
+        let givens = Generator::new(9, 70, Symmetry::Rotational).unwrap().generate();
+        let mut board = Board::from_givens(&givens).unwrap();
+        assert_eq!(board.count_solutions(2), 1);
+        board.unique = true;
+        Solver::default().solve(&mut board).unwrap();
+        let score = rating::from_log(&board.log).score;
+
+        let rated = Generator::new(9, 70, Symmetry::Rotational).unwrap().generate_rated(score, score, 1);
+        assert_eq!(rated, Some(givens));
+    }
+}
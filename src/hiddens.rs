@@ -1,27 +1,79 @@
 use crate::{
     board::{Board, Cell},
     defaults::default_cell,
-    SIZE,
+    misc::{cells, is_set},
+    solver::Technique,
+    step::Deduction,
 };
 
+pub struct HiddenSingles;
+
+pub struct Hiddens<const S: usize>;
+
 #[derive(Debug, Clone)]
 pub struct Group<const S: usize> {
     pub cells: [Cell; S],
-    pub vals: u16,
+    pub vals: u32,
 }
 
+impl Technique for HiddenSingles {
+    fn name(&self) -> &'static str {
+        "Hidden Single"
+    }
+
+    fn difficulty(&self) -> f32 {
+        1.5
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        cells(board)
+            .iter()
+            .filter_map(|cell| {
+                let val = board.get_hidden_single(*cell)?;
+                let mut deduction = Deduction::default();
+                #[allow(clippy::cast_possible_truncation)]
+                deduction.placed.push((*cell, val as u32));
+                Some(deduction)
+            })
+            .collect()
+    }
+}
+
+macro_rules! impl_hiddens {
+    ($size:literal, $name:literal, $difficulty:literal, $from_board:ident) => {
+        impl Technique for Hiddens<$size> {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                $from_board(board).iter().map(|group| group.deduction(board)).collect()
+            }
+        }
+    };
+}
+
+impl_hiddens!(2, "Hidden Pair", 3.4, from_board2);
+impl_hiddens!(3, "Hidden Triple", 4.0, from_board3);
+impl_hiddens!(4, "Hidden Quad", 5.4, from_board4);
+
 pub fn from_board2(board: &Board) -> Vec<Group<2>> {
-    (0..SIZE)
-        .flat_map(|row| (0..(SIZE - 1)).flat_map(move |col_a| ((col_a + 1)..SIZE).map(move |col_b| [Cell { row, col: col_a }, Cell { row, col: col_b }])))
+    let size = board.size;
+    (0..size)
+        .flat_map(|row| (0..(size - 1)).flat_map(move |col_a| ((col_a + 1)..size).map(move |col_b| [Cell { row, col: col_a }, Cell { row, col: col_b }])))
         .filter(|cells| board.no_known(cells))
         .filter_map(|cells| {
             let mut other_values = 0;
-            for col in 0..SIZE {
+            for col in 0..size {
                 if col != cells[0].col && col != cells[1].col {
                     other_values |= board.cells[cells[0].row][col];
                 }
             }
-            let vals = !other_values & default_cell();
+            let vals = !other_values & default_cell(size);
             if vals.count_ones() == 2 {
                 Some(Group { cells, vals })
             } else {
@@ -29,17 +81,17 @@ pub fn from_board2(board: &Board) -> Vec<Group<2>> {
             }
         })
         .chain(
-            (0..SIZE)
-                .flat_map(|col| (0..(SIZE - 1)).flat_map(move |row_a| ((row_a + 1)..SIZE).map(move |row_b| [Cell { row: row_a, col }, Cell { row: row_b, col }])))
+            (0..size)
+                .flat_map(|col| (0..(size - 1)).flat_map(move |row_a| ((row_a + 1)..size).map(move |row_b| [Cell { row: row_a, col }, Cell { row: row_b, col }])))
                 .filter(|cells| board.no_known(cells))
                 .filter_map(|cells| {
                     let mut other_values = 0;
-                    for row in 0..SIZE {
+                    for row in 0..size {
                         if row != cells[0].row && row != cells[1].row {
                             other_values |= board.cells[row][cells[0].col];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 2 {
                         Some(Group { cells, vals })
                     } else {
@@ -58,7 +110,7 @@ pub fn from_board2(board: &Board) -> Vec<Group<2>> {
                             other_values |= board[region[cell]];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 2 {
                         Some(Group {
                             cells: [region[cell_a], region[cell_b]],
@@ -73,21 +125,22 @@ pub fn from_board2(board: &Board) -> Vec<Group<2>> {
 }
 
 pub fn from_board3(board: &Board) -> Vec<Group<3>> {
-    (0..SIZE)
+    let size = board.size;
+    (0..size)
         .flat_map(|row| {
-            (0..(SIZE - 2)).flat_map(move |col_a| {
-                ((col_a + 1)..(SIZE - 1)).flat_map(move |col_b| ((col_b + 1)..SIZE).map(move |col_c| [Cell { row, col: col_a }, Cell { row, col: col_b }, Cell { row, col: col_c }]))
+            (0..(size - 2)).flat_map(move |col_a| {
+                ((col_a + 1)..(size - 1)).flat_map(move |col_b| ((col_b + 1)..size).map(move |col_c| [Cell { row, col: col_a }, Cell { row, col: col_b }, Cell { row, col: col_c }]))
             })
         })
         .filter(|cells| board.no_known(cells))
         .filter_map(|cells| {
             let mut other_values = 0;
-            for col in 0..SIZE {
+            for col in 0..size {
                 if col != cells[0].col && col != cells[1].col && col != cells[2].col {
                     other_values |= board.cells[cells[0].row][col];
                 }
             }
-            let vals = !other_values & default_cell();
+            let vals = !other_values & default_cell(size);
             if vals.count_ones() == 3 {
                 Some(Group { cells, vals })
             } else {
@@ -95,21 +148,21 @@ pub fn from_board3(board: &Board) -> Vec<Group<3>> {
             }
         })
         .chain(
-            (0..SIZE)
+            (0..size)
                 .flat_map(|col| {
-                    (0..(SIZE - 2)).flat_map(move |row_a| {
-                        ((row_a + 1)..(SIZE - 1)).flat_map(move |row_b| ((row_b + 1)..SIZE).map(move |row_c| [Cell { row: row_a, col }, Cell { row: row_b, col }, Cell { row: row_c, col }]))
+                    (0..(size - 2)).flat_map(move |row_a| {
+                        ((row_a + 1)..(size - 1)).flat_map(move |row_b| ((row_b + 1)..size).map(move |row_c| [Cell { row: row_a, col }, Cell { row: row_b, col }, Cell { row: row_c, col }]))
                     })
                 })
                 .filter(|cells| board.no_known(cells))
                 .filter_map(|cells| {
                     let mut other_values = 0;
-                    for row in 0..SIZE {
+                    for row in 0..size {
                         if row != cells[0].row && row != cells[1].row && row != cells[2].row {
                             other_values |= board.cells[row][cells[0].col];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 3 {
                         Some(Group { cells, vals })
                     } else {
@@ -128,7 +181,7 @@ pub fn from_board3(board: &Board) -> Vec<Group<3>> {
                             other_values |= board[region[cell]];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 3 {
                         Some(Group {
                             cells: [region[cell_a], region[cell_b], region[cell_c]],
@@ -143,24 +196,25 @@ pub fn from_board3(board: &Board) -> Vec<Group<3>> {
 }
 
 pub fn from_board4(board: &Board) -> Vec<Group<4>> {
-    (0..SIZE)
+    let size = board.size;
+    (0..size)
         .flat_map(|row| {
-            (0..(SIZE - 3)).flat_map(move |col_a| {
-                ((col_a + 1)..(SIZE - 2)).flat_map(move |col_b| {
-                    ((col_b + 1)..(SIZE - 1))
-                        .flat_map(move |col_c| ((col_c + 1)..SIZE).map(move |col_d| [Cell { row, col: col_a }, Cell { row, col: col_b }, Cell { row, col: col_c }, Cell { row, col: col_d }]))
+            (0..(size - 3)).flat_map(move |col_a| {
+                ((col_a + 1)..(size - 2)).flat_map(move |col_b| {
+                    ((col_b + 1)..(size - 1))
+                        .flat_map(move |col_c| ((col_c + 1)..size).map(move |col_d| [Cell { row, col: col_a }, Cell { row, col: col_b }, Cell { row, col: col_c }, Cell { row, col: col_d }]))
                 })
             })
         })
         .filter(|cells| board.no_known(cells))
         .filter_map(|cells| {
             let mut other_values = 0;
-            for col in 0..SIZE {
+            for col in 0..size {
                 if col != cells[0].col && col != cells[1].col && col != cells[2].col && col != cells[3].col {
                     other_values |= board.cells[cells[0].row][col];
                 }
             }
-            let vals = !other_values & default_cell();
+            let vals = !other_values & default_cell(size);
             if vals.count_ones() == 4 {
                 Some(Group { cells, vals })
             } else {
@@ -168,24 +222,24 @@ pub fn from_board4(board: &Board) -> Vec<Group<4>> {
             }
         })
         .chain(
-            (0..SIZE)
+            (0..size)
                 .flat_map(|col| {
-                    (0..(SIZE - 3)).flat_map(move |row_a| {
-                        ((row_a + 1)..(SIZE - 2)).flat_map(move |row_b| {
-                            ((row_b + 1)..(SIZE - 1))
-                                .flat_map(move |row_c| ((row_c + 1)..SIZE).map(move |row_d| [Cell { row: row_a, col }, Cell { row: row_b, col }, Cell { row: row_c, col }, Cell { row: row_d, col }]))
+                    (0..(size - 3)).flat_map(move |row_a| {
+                        ((row_a + 1)..(size - 2)).flat_map(move |row_b| {
+                            ((row_b + 1)..(size - 1))
+                                .flat_map(move |row_c| ((row_c + 1)..size).map(move |row_d| [Cell { row: row_a, col }, Cell { row: row_b, col }, Cell { row: row_c, col }, Cell { row: row_d, col }]))
                         })
                     })
                 })
                 .filter(|cells| board.no_known(cells))
                 .filter_map(|cells| {
                     let mut other_values = 0;
-                    for row in 0..SIZE {
+                    for row in 0..size {
                         if row != cells[0].row && row != cells[1].row && row != cells[2].row && row != cells[3].row {
                             other_values |= board.cells[row][cells[0].col];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 4 {
                         Some(Group { cells, vals })
                     } else {
@@ -197,7 +251,7 @@ pub fn from_board4(board: &Board) -> Vec<Group<4>> {
             (0..(region.len() - 3))
                 .flat_map(move |cell_a| {
                     ((cell_a + 1)..(region.len() - 2))
-                        .flat_map(move |cell_b| ((cell_b + 1)..(region.len() - 1)).flat_map(move |cell_c| ((cell_c + 1)..SIZE).map(move |cell_d| (cell_a, cell_b, cell_c, cell_d))))
+                        .flat_map(move |cell_b| ((cell_b + 1)..(region.len() - 1)).flat_map(move |cell_c| ((cell_c + 1)..size).map(move |cell_d| (cell_a, cell_b, cell_c, cell_d))))
                 })
                 .filter(|(cell_a, cell_b, cell_c, cell_d)| board.no_known(&[region[*cell_a], region[*cell_b], region[*cell_c], region[*cell_d]]))
                 .filter_map(|(cell_a, cell_b, cell_c, cell_d)| {
@@ -207,7 +261,7 @@ pub fn from_board4(board: &Board) -> Vec<Group<4>> {
                             other_values |= board[region[cell]];
                         }
                     }
-                    let vals = !other_values & default_cell();
+                    let vals = !other_values & default_cell(size);
                     if vals.count_ones() == 4 {
                         Some(Group {
                             cells: [region[cell_a], region[cell_b], region[cell_c], region[cell_d]],
@@ -221,6 +275,17 @@ pub fn from_board4(board: &Board) -> Vec<Group<4>> {
         .collect()
 }
 
+impl<const S: usize> Group<S> {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.to_vec(), self.vals);
+        for cell in self.cells {
+            #[allow(clippy::cast_possible_truncation)]
+            deduction.eliminated.extend((1..=board.size).filter(|d| !is_set!(self.vals, d)).map(|d| (cell, d as u32)));
+        }
+        deduction
+    }
+}
+
 impl Board {
     fn no_known(&self, cells: &[Cell]) -> bool {
         cells.iter().all(|cell| !self[*cell].is_power_of_two())
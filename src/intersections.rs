@@ -2,66 +2,227 @@ use std::rc::Rc;
 
 use crate::{
     board::{Board, Cell, Region},
-    misc::{is_set, units, Unit},
-    SIZE,
+    misc::{almost_locked_sets, box_lines, combinations, digits, is_set, units, BoxLine, Unit},
+    solver::Technique,
+    step::Deduction,
 };
 
-#[derive(Debug, Clone, Copy)]
-pub struct Intersection<'a> {
-    origin: Unit<'a>,
-    target: Unit<'a>,
-    val: u16,
-}
+pub struct PointingPairs;
+
+pub struct BoxLineReductions;
+
+pub struct SueDeCoqs;
+
+// Sets outside the intersection are kept small, as larger ones rarely add eliminations
+const MAX_SET_SIZE: usize = 3;
 
 #[derive(Debug, Clone)]
 pub struct IntersectionTarget {
+    pub overlap: Region,
     pub cells: Region,
-    pub val: u16,
+    pub val: u32,
 }
 
-pub fn from_board(board: &Board) -> Rc<[IntersectionTarget]> {
+#[derive(Debug, Clone)]
+pub struct SueDeCoq {
+    /// Cells from the intersection, then from the rest of the line and the rest of the box.
+    pub cells: Vec<Cell>,
+    pub vals: u32,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+impl Technique for PointingPairs {
+    fn name(&self) -> &'static str {
+        "Pointing Pair"
+    }
+
+    fn difficulty(&self) -> f32 {
+        2.6
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board, true).iter().map(|intersection| intersection.deduction(board)).collect()
+    }
+}
+
+impl Technique for BoxLineReductions {
+    fn name(&self) -> &'static str {
+        "Box/Line Reduction"
+    }
+
+    fn difficulty(&self) -> f32 {
+        2.8
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board, false).iter().map(|intersection| intersection.deduction(board)).collect()
+    }
+}
+
+impl Technique for SueDeCoqs {
+    fn name(&self) -> &'static str {
+        "Sue de Coq"
+    }
+
+    fn difficulty(&self) -> f32 {
+        5.0
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board_sue_de_coq(board).iter().map(SueDeCoq::deduction).collect()
+    }
+}
+
+/// Finds digits confined to the overlap of two units. A region confining a digit to a line points at the rest of the
+/// line, and a line confining a digit to a region claims it from the rest of the region.
+pub fn from_board(board: &Board, pointing: bool) -> Rc<[IntersectionTarget]> {
+    let size = board.size;
     let units = units(board);
+    let unit_cells: Vec<_> = units.iter().map(|unit| unit.cells(size)).collect();
+    let is_region: Vec<_> = units.iter().map(|unit| matches!(unit, Unit::Reg(_))).collect();
+    let mut out = vec![];
+
+    for (i, origin) in unit_cells.iter().enumerate() {
+        if is_region[i] != pointing {
+            continue;
+        }
+        // Regions point at lines and any other regions they overlap, lines only claim from regions
+        for (j, target) in unit_cells.iter().enumerate() {
+            if j == i || !(pointing || is_region[j]) {
+                continue;
+            }
+            let (overlap, outside): (Region, Region) = origin.iter().partition(|cell| target.contains(cell));
+            if overlap.is_empty() || overlap.iter().any(|cell| board[*cell].count_ones() == 1) {
+                continue;
+            }
+
+            let confined = overlap.iter().fold(0, |acc, cell| acc | board[*cell]) & !outside.iter().fold(0, |acc, cell| acc | board[*cell]);
+            for val in digits(confined) {
+                out.push(IntersectionTarget {
+                    overlap: overlap.clone(),
+                    cells: target.iter().filter(|cell| !overlap.contains(cell)).copied().collect(),
+                    val,
+                });
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Finds two or three intersection cells which, with an almost locked set from the rest of the line and one from the
+/// rest of the box sharing no digits, hold as many digits as cells. Each digit then appears exactly once in the pattern,
+/// so digits outside the box set are confined to the line and digits outside the line set are confined to the box.
+pub fn from_board_sue_de_coq(board: &Board) -> Rc<[SueDeCoq]> {
+    let mut out = vec![];
+
+    for BoxLine { region, line, overlap } in box_lines(board) {
+        let overlap: Vec<_> = overlap.into_iter().filter(|cell| board[*cell].count_ones() > 1).collect();
+        if overlap.len() < 2 {
+            continue;
+        }
+
+        let line_rest: Vec<_> = line.iter().filter(|cell| !region.contains(cell)).copied().collect();
+        let box_rest: Vec<_> = region.iter().filter(|cell| !line.contains(cell)).copied().collect();
+        let line_sets = almost_locked_sets(board, &line_rest, MAX_SET_SIZE);
+        let box_sets = almost_locked_sets(board, &box_rest, MAX_SET_SIZE);
+
+        for chosen in (2..=3).flat_map(|size| combinations(&overlap, size)) {
+            let vals = chosen.iter().fold(0, |acc, cell| acc | board[*cell]);
+            if (vals.count_ones() as usize) < chosen.len() + 2 {
+                continue;
+            }
+
+            for (line_set, line_vals) in line_sets.iter().filter(|(_, line_vals)| line_vals & vals != 0) {
+                for (box_set, box_vals) in box_sets.iter().filter(|(_, box_vals)| box_vals & vals != 0 && box_vals & line_vals == 0) {
+                    let all = vals | line_vals | box_vals;
+                    if all.count_ones() as usize != chosen.len() + line_set.len() + box_set.len() {
+                        continue;
+                    }
+
+                    let mut cells = chosen.clone();
+                    cells.extend(line_set);
+                    cells.extend(box_set);
+                    let mut eliminated = eliminations(board, &line, &cells, all & !box_vals);
+                    eliminated.extend(eliminations(board, &region, &cells, all & !line_vals));
+                    if !eliminated.is_empty() {
+                        out.push(SueDeCoq { cells, vals: all, eliminated });
+                    }
+                }
+            }
+        }
+    }
 
-    units
+    out.into()
+}
+
+fn eliminations(board: &Board, unit_cells: &[Cell], pattern: &[Cell], vals: u32) -> Vec<(Cell, u32)> {
+    unit_cells
         .iter()
-        .zip(1..)
-        .flat_map(|(a, i)| units[i..].iter().map(move |b| Intersection { origin: *a, target: *b, val: 0 }))
-        .filter(|intersection| !matches!(intersection.origin, Unit::Row(_) | Unit::Col(_) if !matches!(intersection.target, Unit::Reg(_))))
-        .flat_map(|intersection| {
-            #[allow(clippy::cast_possible_truncation)]
-            (1..=SIZE)
-                .map(move |val| Intersection {
-                    origin: intersection.origin,
-                    target: intersection.target,
-                    val: val as u16,
-                })
-                .filter_map(|intersection| intersection.is_valid(board))
-        })
+        .filter(|cell| !pattern.contains(cell))
+        .flat_map(|cell| digits(vals & board[*cell]).map(move |val| (*cell, val)))
         .collect()
 }
 
-impl Intersection<'_> {
-    fn is_valid(&self, board: &Board) -> Option<IntersectionTarget> {
-        let origin_cells: Region = match self.origin {
-            Unit::Row(row) => (0..SIZE).map(|col| Cell { row, col }).collect(),
-            Unit::Col(col) => (0..SIZE).map(|row| Cell { row, col }).collect(),
-            Unit::Reg(reg) => reg.clone(),
-        };
-        let target_cells: Region = match self.target {
-            Unit::Row(row) => (0..SIZE).map(|col| Cell { row, col }).collect(),
-            Unit::Col(col) => (0..SIZE).map(|row| Cell { row, col }).collect(),
-            Unit::Reg(reg) => reg.clone(),
-        };
-
-        let overlap: Vec<_> = origin_cells.iter().filter(|cell| target_cells.contains(cell)).collect();
-
-        if overlap.iter().all(|cell| board[**cell].count_ones() > 1) && origin_cells.iter().all(|cell| overlap.contains(&cell) || !is_set!(board[*cell], self.val)) {
-            Some(IntersectionTarget {
-                cells: target_cells.iter().filter(|cell| !overlap.contains(cell)).copied().collect(),
-                val: self.val,
-            })
-        } else {
-            None
+impl IntersectionTarget {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let cells = self.overlap.iter().filter(|cell| is_set!(board[**cell], self.val)).copied().collect();
+        let mut deduction = Deduction::new(cells, 1 << self.val);
+        deduction.eliminated.extend(self.cells.iter().map(|cell| (*cell, self.val)));
+        deduction
+    }
+}
+
+impl SueDeCoq {
+    pub fn deduction(&self) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.clone(), self.vals);
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_line_reduction_claims_from_the_box() {
+        // 5 is confined to the first two cells of row 0, both in the top left box
+        let mut board = Board::new(9);
+        for col in 2..9 {
+            board[Cell { row: 0, col }] &= !(1 << 5);
         }
+
+        let deductions = BoxLineReductions.find(&board);
+        assert_eq!(deductions.len(), 1);
+        let mut eliminated = deductions[0].eliminated.clone();
+        eliminated.sort_unstable();
+        let expected: Vec<_> = (1..3).flat_map(|row| (0..3).map(move |col| (Cell { row, col }, 5))).collect();
+        assert_eq!(eliminated, expected);
+        assert!(PointingPairs.find(&board).is_empty());
+    }
+
+    #[test]
+    fn sue_de_coq_splits_digits_between_line_and_box() {
+        // r1c1 and r1c2 hold 1 to 4, with {1, 2} in r1c6 on the line and {3, 4} in r2c1 in the box
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4;
+        board[Cell { row: 0, col: 1 }] = 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4;
+        board[Cell { row: 0, col: 5 }] = 1 << 1 | 1 << 2;
+        board[Cell { row: 1, col: 0 }] = 1 << 3 | 1 << 4;
+
+        let found = from_board_sue_de_coq(&board);
+        assert_eq!(found.len(), 1);
+        let mut eliminated = found[0].eliminated.clone();
+        eliminated.sort_unstable();
+
+        let line = [2, 3, 4, 6, 7, 8].map(|col| Cell { row: 0, col }).into_iter().flat_map(|cell| [(cell, 1), (cell, 2)]);
+        let region = [(0, 2), (1, 1), (1, 2), (2, 0), (2, 1), (2, 2)]
+            .map(|(row, col)| Cell { row, col })
+            .into_iter()
+            .flat_map(|cell| [(cell, 3), (cell, 4)]);
+        let mut expected: Vec<_> = line.chain(region).collect();
+        expected.sort_unstable();
+        assert_eq!(eliminated, expected);
     }
 }
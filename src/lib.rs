@@ -0,0 +1,56 @@
+//! A logical sudoku solver which records every deduction it makes, for grids from 4x4 up to 25x25.
+//!
+//! Puzzles are read with `format::parse_givens`, turned into a `Board` with `Board::from_givens` and solved by a
+//! `Solver`, whose pipeline of techniques can be reordered or extended. Each deduction is logged on the board as a
+//! `Step`, from which `rating::from_log` grades the puzzle.
+
+mod aics;
+mod aligned;
+mod als;
+mod board;
+mod bug;
+mod colouring;
+mod defaults;
+mod error;
+mod forcing;
+pub mod format;
+pub mod generate;
+mod hiddens;
+mod intersections;
+mod misc;
+mod nakeds;
+pub mod rating;
+mod rectangles;
+mod search;
+mod solver;
+mod step;
+mod turbots;
+mod unique_rectangles;
+mod wings;
+mod xcycles;
+mod xwings;
+mod xychains;
+mod ywings;
+
+pub use aics::Aics;
+pub use aligned::AlignedExclusion;
+pub use als::{AlsXyWings, AlsXz, DeathBlossoms};
+pub use board::{Board, Cell, Givens};
+pub use bug::Bugs;
+pub use colouring::{Medusa, SimpleColouring};
+pub use error::SolveError;
+pub use forcing::{CellForcingChains, DigitForcingChains, Nishio, UnitForcingChains};
+pub use hiddens::{HiddenSingles, Hiddens};
+pub use intersections::{BoxLineReductions, PointingPairs, SueDeCoqs};
+pub use nakeds::Nakeds;
+pub use rectangles::Rectangles;
+pub use search::SolvedBy;
+pub use solver::{Solver, Technique};
+pub use step::{Deduction, Step, StepDisplay};
+pub use turbots::{Skyscrapers, TurbotFish, TwoStringKites};
+pub use unique_rectangles::{AvoidableRectangles, HiddenUniqueRectangles, UniqueRectangles};
+pub use wings::{WXYZWings, XYZWings};
+pub use xcycles::XCycles;
+pub use xwings::{FinnedXWings, FrankenFish, MutantFish, XWings};
+pub use xychains::XYChains;
+pub use ywings::YWings;
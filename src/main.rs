@@ -1,60 +1,150 @@
-#![allow(incomplete_features)]
-#![feature(generic_const_exprs)]
-
-use std::{env::args, fs::read_to_string, io, path::Path, time::Instant};
+use std::{
+    env::args,
+    fs::read_to_string,
+    io,
+    path::Path,
+    process::exit,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
-use crate::{
-    board::{Board, Cell},
-    format::format,
+use sudoku_solver_v2::{
+    format::{format, format_givens, parse_puzzles},
+    generate::{Generator, Symmetry},
+    rating,
+    Board, SolveError, SolvedBy, Solver,
 };
 
-mod board;
-mod colouring;
-mod defaults;
-mod format;
-mod hiddens;
-mod intersections;
-mod misc;
-mod nakeds;
-mod rectangles;
-mod xwings;
-mod ywings;
+fn main() {
+    let (flags, paths): (Vec<_>, Vec<_>) = args().skip(1).partition(|arg| arg.starts_with('-'));
+    let show_steps = flags.iter().any(|flag| flag == "--steps");
+    let use_search = flags.iter().any(|flag| flag == "--search");
+    let count_only = flags.iter().any(|flag| flag == "--count");
+    let zero_based = flags.iter().any(|flag| flag == "--zero-based");
+
+    if let Some(count) = flag_value(&flags, "--generate") {
+        generate(&flags, count.parse().expect("Puzzle count must be a number"));
+        return;
+    }
 
-const SIZE: usize = 9;
+    let mut solver = Solver::default();
+    if let Some(disabled) = flag_value(&flags, "--disable") {
+        solver.techniques.retain(|technique| !disabled.split(',').any(|name| name == technique.name()));
+    }
+
+    for (puzzle, i) in read_puzzle_file(Path::new(paths.first().expect("Must pass at least one argument").as_str()), zero_based)
+        .expect("Error reading puzzle file")
+        .into_iter()
+        .zip(1..)
+    {
+        let mut board = match puzzle {
+            Ok(board) => board,
+            Err(err) => {
+                println!("Puzzle {i} is invalid: {err}");
+                continue;
+            }
+        };
+
+        let solutions = board.count_solutions(2);
+        if count_only {
+            match solutions {
+                0 => println!("Puzzle {i}: no solutions"),
+                1 => println!("Puzzle {i}: 1 solution"),
+                _ => println!("Puzzle {i}: at least {solutions} solutions"),
+            }
+            continue;
+        }
+        match solutions {
+            0 => {
+                println!("Puzzle {i} has no solution");
+                continue;
+            }
+            1 => board.unique = true,
+            _ => println!("Puzzle {i} has multiple solutions, uniqueness techniques are disabled"),
+        }
 
-fn main() {
-    for mut board in read_puzzle_file(Path::new(args().nth(1).expect("Must pass at least one argument").as_str())).expect("Error reading puzzle file") {
         let start = Instant::now();
-        board.solve();
+        let result = if use_search {
+            solver.solve_with_search(&mut board).map(Some)
+        } else {
+            solver.solve(&mut board).map(|()| None)
+        };
         let elapsed = start.elapsed();
+        let solved_by = match result {
+            Ok(solved_by) => solved_by,
+            Err(err) => {
+                println!("Puzzle {i} has no solution: {err}");
+                continue;
+            }
+        };
+        if show_steps {
+            for step in &board.log {
+                println!("{}", step.display(board.size));
+            }
+        }
         println!("{}", format(&board).unwrap());
-        println!("Elapsed time: {elapsed:?}");
-        if !board.is_solved() {
-            break;
+        if solved_by == Some(SolvedBy::Search) {
+            println!("Logical techniques stalled, finished by search");
+        }
+        let rating = rating::from_log(&board.log);
+        if board.is_solved() && solved_by != Some(SolvedBy::Search) {
+            println!("Difficulty: {rating}");
+        } else {
+            println!("Difficulty: above {rating}");
         }
+        println!("Elapsed time: {elapsed:?}");
     }
 }
 
-fn read_puzzle_file(path: &Path) -> io::Result<Vec<Board>> {
-    let raw = read_to_string(path)?;
-
-    let data = if let Some(data) = raw.split_once("END") { data.0 } else { &raw };
+fn generate(flags: &[String], count: usize) {
+    let seed = flag_value(flags, "--seed").map_or_else(
+        || SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+        |seed| seed.parse().expect("Seed must be a number"),
+    );
+    let symmetry = match flag_value(flags, "--symmetry") {
+        None | Some("none") => Symmetry::None,
+        Some("rotational") => Symmetry::Rotational,
+        Some("mirror") => Symmetry::Mirror,
+        Some(other) => panic!("Unknown symmetry {other}"),
+    };
+    let band = flag_value(flags, "--rating").map(|band| {
+        let (min, max) = band.split_once('-').expect("Rating band must be given as MIN-MAX");
+        (min.parse().expect("Rating must be a number"), max.parse().expect("Rating must be a number"))
+    });
 
-    Ok(data
-        .trim()
-        .split("\n\n")
-        .map(|puzzle| {
-            let lines: Vec<_> = puzzle.split('\n').collect();
-            let mut board = Board::new();
+    let size = flag_value(flags, "--size").map_or(9, |size| size.parse().expect("Size must be a number"));
+    let attempts = 100;
 
-            for (line, row) in lines.iter().zip(0..) {
-                for (val, col) in line.chars().zip(0..).filter_map(|(chr, col)| chr.to_digit(16).map(|d| (d, col))) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    board.place_digit(val as u16, Cell { row, col });
-                }
+    let mut generator = match Generator::new(size, seed, symmetry) {
+        Ok(generator) => generator,
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    };
+    let puzzles: Vec<_> = (0..count)
+        .filter_map(|_| {
+            if let Some((min, max)) = band {
+                generator.generate_rated(min, max, attempts)
+            } else {
+                Some(generator.generate())
             }
-
-            board
         })
-        .collect())
+        .map(|givens| format_givens(&givens))
+        .collect();
+    println!("{}", puzzles.join("\n\n"));
+
+    if puzzles.len() < count {
+        if let Some((min, max)) = band {
+            eprintln!("Only generated {} of {count} puzzles rated {min}-{max}, each given {attempts} attempts", puzzles.len());
+        }
+        exit(1);
+    }
+}
+
+fn flag_value<'a>(flags: &'a [String], name: &str) -> Option<&'a str> {
+    flags.iter().find_map(|flag| flag.strip_prefix(name)?.strip_prefix('='))
+}
+
+fn read_puzzle_file(path: &Path, zero_based: bool) -> io::Result<Vec<Result<Board, SolveError>>> {
+    Ok(parse_puzzles(&read_to_string(path)?, zero_based).iter().map(Board::from_givens).collect())
 }
@@ -1,9 +1,6 @@
 use std::rc::Rc;
 
-use crate::{
-    board::{Board, Cell, Region},
-    SIZE,
-};
+use crate::board::{Board, Cell, Region};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Unit<'a> {
@@ -13,11 +10,155 @@ pub enum Unit<'a> {
 }
 
 pub fn units(board: &'_ Board) -> Rc<[Unit<'_>]> {
-    board.regions.iter().map(Unit::Reg).chain((0..SIZE).map(Unit::Row)).chain((0..SIZE).map(Unit::Col)).collect()
+    board
+        .regions
+        .iter()
+        .map(Unit::Reg)
+        .chain((0..board.size).map(Unit::Row))
+        .chain((0..board.size).map(Unit::Col))
+        .collect()
 }
 
-pub fn cells() -> Rc<[Cell]> {
-    (0..SIZE).flat_map(|row| (0..SIZE).map(move |col| Cell { row, col })).collect()
+pub fn cells(board: &Board) -> Rc<[Cell]> {
+    let size = board.size;
+    (0..size).flat_map(|row| (0..size).map(move |col| Cell { row, col })).collect()
+}
+
+pub fn bivalue_cells(board: &Board) -> Vec<Cell> {
+    cells(board).iter().copied().filter(|cell| board[*cell].count_ones() == 2).collect()
+}
+
+/// Pairs of unsolved cells which are the only two places for `val` in some unit, each pair listed once.
+pub fn conjugate_pairs(board: &Board, val: u32) -> Vec<(Cell, Cell)> {
+    let mut pairs: Vec<_> = units(board)
+        .iter()
+        .filter_map(|unit| {
+            let holding: Vec<_> = unit.cells(board.size).into_iter().filter(|cell| is_set!(board[*cell], val)).collect();
+            match holding[..] {
+                [a, b] if board[a].count_ones() > 1 && board[b].count_ones() > 1 => Some((a.min(b), a.max(b))),
+                _ => None,
+            }
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// A region and a row or column which share cells.
+#[derive(Debug, Clone)]
+pub struct BoxLine {
+    pub region: Region,
+    pub line: Region,
+    pub overlap: Region,
+}
+
+/// Every region paired with each row and column it shares cells with.
+pub fn box_lines(board: &Board) -> Vec<BoxLine> {
+    let size = board.size;
+    let lines: Vec<_> = (0..size).map(Unit::Row).chain((0..size).map(Unit::Col)).map(|line| line.cells(size)).collect();
+
+    board
+        .regions
+        .iter()
+        .flat_map(|region| {
+            lines.iter().filter_map(|line| {
+                let overlap: Region = line.iter().filter(|cell| region.contains(cell)).copied().collect();
+                (!overlap.is_empty()).then(|| BoxLine {
+                    region: region.clone(),
+                    line: line.clone(),
+                    overlap,
+                })
+            })
+        })
+        .collect()
+}
+
+/// The digits set in a candidate mask, smallest first.
+pub fn digits(mask: u32) -> impl Iterator<Item = u32> {
+    (1..u32::BITS).filter(move |val| is_set!(mask, val))
+}
+
+/// Every way of picking `k` of `items`, each in the order given.
+pub fn combinations<T: Copy>(items: &[T], k: usize) -> Vec<Vec<T>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    (0..items.len())
+        .flat_map(|i| {
+            combinations(&items[i + 1..], k - 1).into_iter().map(move |mut rest| {
+                rest.insert(0, items[i]);
+                rest
+            })
+        })
+        .collect()
+}
+
+/// Sets of `n` unsolved cells from `cells` holding `n + 1` digits between them, up to `max_size` cells.
+pub fn almost_locked_sets(board: &Board, cells: &[Cell], max_size: usize) -> Vec<(Vec<Cell>, u32)> {
+    let unsolved: Vec<_> = cells.iter().copied().filter(|cell| board[*cell].count_ones() > 1).collect();
+    let mut out = vec![];
+    extend_sets(board, &unsolved, max_size, &mut vec![], 0, &mut out);
+    out
+}
+
+fn extend_sets(board: &Board, unsolved: &[Cell], max_size: usize, set: &mut Vec<Cell>, vals: u32, out: &mut Vec<(Vec<Cell>, u32)>) {
+    if !set.is_empty() && vals.count_ones() as usize == set.len() + 1 {
+        out.push((set.clone(), vals));
+    }
+    if set.len() == max_size {
+        return;
+    }
+
+    for (i, cell) in unsolved.iter().enumerate() {
+        let next = vals | board[*cell];
+        if next.count_ones() as usize <= max_size + 1 {
+            set.push(*cell);
+            extend_sets(board, &unsolved[i + 1..], max_size, set, next, out);
+            set.pop();
+        }
+    }
+}
+
+/// Which cells see each other, worked out once for techniques which ask many times.
+pub struct Visibility {
+    size: usize,
+    sees: Vec<bool>,
+}
+
+impl Visibility {
+    /// Matches `Cell::can_see`, including cells sharing any of several overlapping regions.
+    pub fn new(board: &Board) -> Self {
+        let size = board.size;
+        let index = |cell: &Cell| cell.row * size + cell.col;
+        let cells = cells(board);
+        let mut sees: Vec<_> = cells.iter().flat_map(|a| cells.iter().map(move |b| a.row == b.row || a.col == b.col)).collect();
+
+        for region in &board.regions {
+            for a in region {
+                for b in region {
+                    sees[index(a) * size * size + index(b)] = true;
+                }
+            }
+        }
+
+        Visibility { size, sees }
+    }
+
+    pub fn get(&self, a: Cell, b: Cell) -> bool {
+        let cells = self.size * self.size;
+        self.sees[(a.row * self.size + a.col) * cells + b.row * self.size + b.col]
+    }
+}
+
+impl Unit<'_> {
+    pub fn cells(&self, size: usize) -> Region {
+        match self {
+            Unit::Row(row) => (0..size).map(|col| Cell { row: *row, col }).collect(),
+            Unit::Col(col) => (0..size).map(|row| Cell { row, col: *col }).collect(),
+            Unit::Reg(reg) => (*reg).clone(),
+        }
+    }
 }
 
 macro_rules! is_set {
@@ -27,3 +168,24 @@ macro_rules! is_set {
 }
 
 pub(crate) use is_set;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visibility_matches_can_see_with_overlapping_regions() {
+        // The default boxes, plus both diagonals
+        let mut regions = Board::new(9).regions;
+        regions.push((0..9).map(|i| Cell { row: i, col: i }).collect());
+        regions.push((0..9).map(|i| Cell { row: i, col: 8 - i }).collect());
+        let board = Board::new_custom_regions(9, regions);
+
+        let sees = Visibility::new(&board);
+        for a in cells(&board).iter() {
+            for b in cells(&board).iter() {
+                assert_eq!(sees.get(*a, *b), a.can_see(&board, b), "{a} and {b}");
+            }
+        }
+    }
+}
@@ -5,14 +5,21 @@ use std::{
 
 use crate::{
     board::{get_regions_with_cells, Board, Cell},
-    misc::cells,
+    misc::{cells, digits},
+    solver::Technique,
+    step::Deduction,
 };
 
+pub const NAKED_SINGLE: &str = "Naked Single";
+pub const NAKED_SINGLE_DIFFICULTY: f32 = 2.3;
+
+pub struct Nakeds<const S: usize>;
+
 #[derive(Debug, Clone)]
 pub struct Group<const S: usize> {
     pub relation: Relation,
     pub cells: [Cell; S],
-    pub vals: u16,
+    pub vals: u32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -46,8 +53,30 @@ macro_rules! expand_group {
     };
 }
 
+macro_rules! impl_nakeds {
+    ($size:literal, $name:literal, $difficulty:literal, $from_board:ident) => {
+        impl Technique for Nakeds<$size> {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                $from_board(board).iter().map(|group| group.deduction(board)).collect()
+            }
+        }
+    };
+}
+
+impl_nakeds!(2, "Naked Pair", 3.0, from_board2);
+impl_nakeds!(3, "Naked Triple", 3.6, from_board3);
+impl_nakeds!(4, "Naked Quad", 5.0, from_board4);
+
 pub fn from_board2(board: &Board) -> Rc<[Group<2>]> {
-    let groups: Vec<_> = cells()
+    let groups: Vec<_> = cells(board)
         .iter()
         .filter_map(|cell| {
             let vals = board[*cell];
@@ -67,7 +96,7 @@ pub fn from_board2(board: &Board) -> Rc<[Group<2>]> {
 }
 
 pub fn from_board3(board: &Board) -> Rc<[Group<3>]> {
-    let groups: Vec<_> = cells()
+    let groups: Vec<_> = cells(board)
         .iter()
         .filter_map(|cell| {
             let vals = board[*cell];
@@ -87,7 +116,7 @@ pub fn from_board3(board: &Board) -> Rc<[Group<3>]> {
 }
 
 pub fn from_board4(board: &Board) -> Rc<[Group<4>]> {
-    let groups: Vec<_> = cells()
+    let groups: Vec<_> = cells(board)
         .iter()
         .filter_map(|cell| {
             let vals = board[*cell];
@@ -108,7 +137,39 @@ pub fn from_board4(board: &Board) -> Rc<[Group<4>]> {
         .collect()
 }
 
-impl<const S: usize> Group<{ S }> {
+/// Extends a group by one cell. Implemented for each size separately, since returning `Group<{ S + 1 }>` from a
+/// generic impl needs the unstable `generic_const_exprs` feature.
+trait Grow {
+    type Grown;
+
+    fn add(&self, rhs: &Group<1>) -> Self::Grown;
+}
+
+macro_rules! impl_grow {
+    ($size:literal, $grown:literal) => {
+        impl Grow for Group<$size> {
+            type Grown = Group<$grown>;
+
+            fn add(&self, rhs: &Group<1>) -> Self::Grown {
+                let mut cells = [Cell::default(); $grown];
+                cells[..$size].copy_from_slice(&self.cells);
+                cells[$size..].copy_from_slice(&rhs.cells);
+
+                Group {
+                    relation: self.relation + rhs.relation,
+                    cells,
+                    vals: self.vals | rhs.vals,
+                }
+            }
+        }
+    };
+}
+
+impl_grow!(1, 2);
+impl_grow!(2, 3);
+impl_grow!(3, 4);
+
+impl<const S: usize> Group<S> {
     pub fn calc_relations(self, board: &Board) -> Self {
         let mut regs = get_regions_with_cells!(board, self.cells);
         Self {
@@ -122,20 +183,29 @@ impl<const S: usize> Group<{ S }> {
         }
     }
 
-    pub fn no_repeats(&self) -> bool {
-        self.cells.iter().zip(1..).all(|(c, i)| !self.cells[i..].contains(c))
-    }
-
-    fn add(&self, rhs: &Group<1>) -> Group<{ S + 1 }> {
-        let mut cells = [Cell::default(); S + 1];
-        cells[..S].copy_from_slice(&self.cells);
-        cells[S..].copy_from_slice(&rhs.cells);
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let size = board.size;
+        let mut peers = vec![];
+        if self.relation.row {
+            peers.extend((0..size).map(|col| Cell { row: self.cells[0].row, col }));
+        } else if self.relation.col {
+            peers.extend((0..size).map(|row| Cell { row, col: self.cells[0].col }));
+        }
+        if self.relation.reg {
+            for region in get_regions_with_cells!(board, self.cells) {
+                peers.extend(region);
+            }
+        }
 
-        Group {
-            relation: self.relation + rhs.relation,
-            cells,
-            vals: self.vals | rhs.vals,
+        let mut deduction = Deduction::new(self.cells.to_vec(), self.vals);
+        for cell in peers.into_iter().filter(|cell| !self.cells.contains(cell)) {
+            deduction.eliminated.extend(digits(self.vals).map(|val| (cell, val)));
         }
+        deduction
+    }
+
+    pub fn no_repeats(&self) -> bool {
+        self.cells.iter().zip(1..).all(|(c, i)| !self.cells[i..].contains(c))
     }
 }
 
@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+use crate::step::Step;
+
+#[derive(Debug, Clone)]
+pub struct Rating {
+    pub score: f32,
+    pub hardest: Option<&'static str>,
+    pub counts: Vec<(&'static str, usize)>,
+}
+
+/// Rates a solve log by its hardest step, as Sudoku Explainer does.
+pub fn from_log(log: &[Step]) -> Rating {
+    let mut counts: Vec<(&'static str, f32, usize)> = vec![];
+    for step in log {
+        if let Some((_, _, count)) = counts.iter_mut().find(|(technique, _, _)| *technique == step.technique) {
+            *count += 1;
+        } else {
+            counts.push((step.technique, step.difficulty, 1));
+        }
+    }
+    counts.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let hardest = counts.last().copied();
+    Rating {
+        score: hardest.map_or(0.0, |(_, difficulty, _)| difficulty),
+        hardest: hardest.map(|(technique, _, _)| technique),
+        counts: counts.into_iter().map(|(technique, _, count)| (technique, count)).collect(),
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}", self.score)?;
+        if let Some(hardest) = self.hardest {
+            let counts: Vec<_> = self.counts.iter().map(|(technique, count)| format!("{count} {technique}")).collect();
+            write!(f, " (hardest: {hardest}; {})", counts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::step::Deduction;
+
+    fn step(technique: &'static str, difficulty: f32) -> Step {
+        Step {
+            technique,
+            difficulty,
+            deduction: Deduction::default(),
+        }
+    }
+
+    #[test]
+    fn empty_log_rates_zero() {
+        let rating = from_log(&[]);
+        assert_eq!(rating.score, 0.0);
+        assert_eq!(rating.hardest, None);
+        assert!(rating.counts.is_empty());
+    }
+
+    #[test]
+    fn singles_rate_as_the_harder_single() {
+        let rating = from_log(&[step("Naked Single", 2.3), step("Hidden Single", 1.5), step("Hidden Single", 1.5)]);
+        assert_eq!(rating.score, 2.3);
+        assert_eq!(rating.hardest, Some("Naked Single"));
+        assert_eq!(rating.counts, [("Hidden Single", 2), ("Naked Single", 1)]);
+    }
+
+    #[test]
+    fn score_is_the_hardest_step_wherever_it_falls() {
+        let log = [step("Hidden Single", 1.5), step("X-Wing", 3.2), step("Naked Pair", 3.0), step("Naked Single", 2.3)];
+        let rating = from_log(&log);
+        assert_eq!(rating.score, 3.2);
+        assert_eq!(rating.hardest, Some("X-Wing"));
+        assert_eq!(rating.counts.iter().map(|(_, count)| count).sum::<usize>(), log.len());
+    }
+}
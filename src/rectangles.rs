@@ -1,16 +1,41 @@
 use crate::{
     board::{Board, Cell},
     misc::is_set,
-    SIZE,
+    solver::Technique,
+    step::Deduction,
 };
 
-pub fn from_board(board: &Board) -> Option<(Vec<Cell>, u16)> {
-    for val in 1..=SIZE {
-        'row: for row in 0..SIZE {
+pub struct Rectangles;
+
+impl Technique for Rectangles {
+    fn name(&self) -> &'static str {
+        "Rectangle Elimination"
+    }
+
+    fn difficulty(&self) -> f32 {
+        6.6
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board)
+            .map(|(pattern, cells, val)| {
+                let mut deduction = Deduction::new(pattern, 1 << val);
+                deduction.eliminated.extend(cells.into_iter().map(|cell| (cell, val)));
+                deduction
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+pub fn from_board(board: &Board) -> Option<(Vec<Cell>, Vec<Cell>, u32)> {
+    let size = board.size;
+    for val in 1..=size {
+        'row: for row in 0..size {
             let mut has_val = [0; 2];
             let mut idx = 0;
 
-            for col in 0..SIZE {
+            for col in 0..size {
                 if is_set!(board.cells[row][col], val) {
                     if idx == 2 {
                         continue 'row;
@@ -33,7 +58,7 @@ pub fn from_board(board: &Board) -> Option<(Vec<Cell>, u16)> {
             for (weak_col, region_col) in has_val.into_iter().zip(has_val.into_iter().rev()) {
                 let mut weak_cells = vec![];
 
-                for weak_row in 0..SIZE {
+                for weak_row in 0..size {
                     if weak_row == row {
                         continue;
                     }
@@ -62,18 +87,18 @@ pub fn from_board(board: &Board) -> Option<(Vec<Cell>, u16)> {
                 }
 
                 if !weak_cells.is_empty() {
-                    return Some((weak_cells, val as u16));
+                    return Some((vec![Cell { row, col: weak_col }, Cell { row, col: region_col }], weak_cells, val as u32));
                 }
             }
         }
     }
 
-    for val in 1..=SIZE {
-        'col: for col in 0..SIZE {
+    for val in 1..=size {
+        'col: for col in 0..size {
             let mut has_val = [0; 2];
             let mut idx = 0;
 
-            for row in 0..SIZE {
+            for row in 0..size {
                 if is_set!(board.cells[row][col], val) {
                     if idx == 2 {
                         continue 'col;
@@ -96,7 +121,7 @@ pub fn from_board(board: &Board) -> Option<(Vec<Cell>, u16)> {
             for (weak_row, region_row) in has_val.into_iter().zip(has_val.into_iter().rev()) {
                 let mut weak_cells = vec![];
 
-                for weak_col in 0..SIZE {
+                for weak_col in 0..size {
                     if weak_col == col {
                         continue;
                     }
@@ -125,7 +150,7 @@ pub fn from_board(board: &Board) -> Option<(Vec<Cell>, u16)> {
                 }
 
                 if !weak_cells.is_empty() {
-                    return Some((weak_cells, val as u16));
+                    return Some((vec![Cell { row: weak_row, col }, Cell { row: region_row, col }], weak_cells, val as u32));
                 }
             }
         }
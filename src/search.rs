@@ -0,0 +1,168 @@
+use crate::{
+    board::{Board, Cell, Region},
+    defaults::default_cell,
+    error::SolveError,
+    misc::{cells, digits, is_set, units},
+    solver::Solver,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvedBy {
+    Logic,
+    Search,
+}
+
+/// Finds a solution by depth-first search, branching on the unsolved cell with the fewest candidates.
+/// Only singles are propagated between branches, as the other techniques cost far more than they prune.
+pub fn search(board: &Board) -> Option<Board> {
+    let board = propagate_singles(board)?;
+    let Some(cell) = branch_cell(&board) else {
+        return Some(board);
+    };
+
+    for val in digits(board[cell]) {
+        let mut branch = board.clone();
+        if branch.place_digit(val, cell).is_ok() {
+            if let Some(solution) = search(&branch) {
+                return Some(solution);
+            }
+        }
+    }
+
+    None
+}
+
+/// Counts solutions in the same way as `search`, stopping once `limit` have been found.
+pub fn count(board: &Board, limit: usize) -> usize {
+    let mut budget = usize::MAX;
+    count_within(board, limit, &mut budget).expect("An unbounded count always finishes")
+}
+
+/// Counts solutions as `count` does, but gives up once `budget` branches have been searched.
+pub fn count_within(board: &Board, limit: usize, budget: &mut usize) -> Option<usize> {
+    if limit == 0 {
+        return Some(0);
+    }
+    *budget = budget.checked_sub(1)?;
+    let Some(board) = propagate_singles(board) else {
+        return Some(0);
+    };
+    let Some(cell) = branch_cell(&board) else {
+        return Some(1);
+    };
+
+    let mut found = 0;
+    for val in digits(board[cell]) {
+        let mut branch = board.clone();
+        if branch.place_digit(val, cell).is_ok() {
+            found += count_within(&branch, limit - found, budget)?;
+            if found >= limit {
+                break;
+            }
+        }
+    }
+
+    Some(found)
+}
+
+/// Places naked and hidden singles until none remain, or returns nothing on a contradiction. Hidden singles are
+/// found from per-unit masks rather than through `HiddenSingles`, as this runs once per branch of the search.
+fn propagate_singles(board: &Board) -> Option<Board> {
+    let mut board = board.clone();
+    board.log.clear();
+
+    let full = default_cell(board.size);
+    let unit_cells: Vec<Region> = units(&board).iter().map(|unit| unit.cells(board.size)).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for unit in &unit_cells {
+            let (mut once, mut twice, mut solved) = (0, 0, 0);
+            for cell in unit {
+                let mask = board[*cell];
+                twice |= once & mask;
+                once |= mask;
+                if mask.is_power_of_two() {
+                    solved |= mask;
+                }
+            }
+            if once != full {
+                return None;
+            }
+
+            for val in digits(once & !twice & !solved) {
+                let cell = *unit.iter().find(|cell| is_set!(board[**cell], val))?;
+                board.place_digit(val, cell).ok()?;
+                changed = true;
+            }
+        }
+    }
+
+    Some(board)
+}
+
+/// Runs `solver` on the board, then checks every digit still has a place in every unit, as the techniques themselves
+/// only notice a cell running out of candidates.
+pub fn propagate(board: &mut Board, solver: &Solver) -> Result<(), SolveError> {
+    solver.solve(board)?;
+
+    let full = default_cell(board.size);
+    for unit in units(board).iter() {
+        let unit_cells = unit.cells(board.size);
+        if unit_cells.iter().fold(0, |acc, cell| acc | board[*cell]) != full {
+            return Err(SolveError::Contradiction {
+                cell: unit_cells[0],
+                reason: "a digit has no place left in the unit",
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn branch_cell(board: &Board) -> Option<Cell> {
+    cells(board).iter().copied().filter(|cell| board[*cell].count_ones() > 1).min_by_key(|cell| board[*cell].count_ones())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::parse_givens;
+
+    const HARD: &str = "8--------\n--36-----\n-7--9-2--\n-5---7---\n----457--\n---1---3-\n--1----68\n--85---1-\n-9----4--";
+
+    #[test]
+    fn search_finishes_what_logic_cannot() {
+        let givens = parse_givens(HARD, false);
+        let mut board = Board::from_givens(&givens).unwrap();
+        Solver::basic().solve(&mut board).unwrap();
+        assert!(!board.is_solved());
+
+        let solution = search(&board).unwrap();
+        assert!(solution.is_solved());
+        for cell in cells(&solution).iter() {
+            assert!(givens[cell.row][cell.col] == 0 || solution[*cell] == 1 << givens[cell.row][cell.col]);
+        }
+        for unit in units(&solution).iter() {
+            assert_eq!(unit.cells(9).iter().fold(0, |acc, cell| acc | solution[*cell]), default_cell(9));
+        }
+    }
+
+    #[test]
+    fn count_tells_none_one_and_many_apart() {
+        // No cell of the first row may hold 1
+        let mut contradictory = Board::new(4);
+        for col in 0..4 {
+            contradictory[Cell { row: 0, col }] &= !(1 << 1);
+        }
+        assert_eq!(count(&contradictory, 2), 0);
+
+        let unique = Board::from_givens(&parse_givens(HARD, false)).unwrap();
+        assert_eq!(count(&unique, 2), 1);
+
+        // An empty 4x4 grid has 288 solutions, of which only as many as the limit are counted
+        let empty = Board::new(4);
+        assert_eq!(count(&empty, 2), 2);
+        assert_eq!(count(&empty, 5), 5);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    aics::Aics,
+    aligned::AlignedExclusion,
+    als::{AlsXyWings, AlsXz, DeathBlossoms},
+    board::Board,
+    bug::Bugs,
+    colouring::{Medusa, SimpleColouring},
+    error::SolveError,
+    forcing::{CellForcingChains, DigitForcingChains, Nishio, UnitForcingChains},
+    hiddens::{HiddenSingles, Hiddens},
+    intersections::{BoxLineReductions, PointingPairs, SueDeCoqs},
+    nakeds::Nakeds,
+    rectangles::Rectangles,
+    search::{self, SolvedBy},
+    step::{Deduction, Step},
+    turbots::{Skyscrapers, TurbotFish, TwoStringKites},
+    unique_rectangles::{AvoidableRectangles, HiddenUniqueRectangles, UniqueRectangles},
+    wings::{WXYZWings, XYZWings},
+    xcycles::XCycles,
+    xwings::{FinnedXWings, FrankenFish, MutantFish, XWings},
+    xychains::XYChains,
+    ywings::YWings,
+};
+
+pub trait Technique {
+    fn name(&self) -> &'static str;
+
+    /// Weight of the technique on the Sudoku Explainer scale.
+    fn difficulty(&self) -> f32;
+
+    fn find(&self, board: &Board) -> Vec<Deduction>;
+
+    /// Techniques returning true are only run once the puzzle is proven to have a single solution.
+    fn assumes_uniqueness(&self) -> bool {
+        false
+    }
+}
+
+pub struct Solver {
+    pub techniques: Vec<Box<dyn Technique>>,
+}
+
+impl Solver {
+    pub fn new(techniques: Vec<Box<dyn Technique>>) -> Self {
+        Solver { techniques }
+    }
+
+    /// Singles, intersections and naked and hidden subsets, cheap enough to propagate every branch of an assumption.
+    pub fn basic() -> Self {
+        Solver::new(vec![
+            Box::new(HiddenSingles),
+            Box::new(PointingPairs),
+            Box::new(BoxLineReductions),
+            Box::new(Nakeds::<2>),
+            Box::new(Hiddens::<2>),
+            Box::new(Nakeds::<3>),
+            Box::new(Hiddens::<3>),
+        ])
+    }
+
+    /// Runs the techniques in order, starting over from the first whenever one makes progress.
+    pub fn solve(&self, board: &mut Board) -> Result<(), SolveError> {
+        'outer: loop {
+            for technique in &self.techniques {
+                if technique.assumes_uniqueness() && !board.unique {
+                    continue;
+                }
+
+                let mut has_changed = false;
+                for deduction in technique.find(board) {
+                    let step = Step {
+                        technique: technique.name(),
+                        difficulty: technique.difficulty(),
+                        deduction,
+                    };
+                    has_changed = board.apply(step)? || has_changed;
+                }
+
+                if has_changed {
+                    continue 'outer;
+                }
+            }
+
+            return Ok(());
+        }
+    }
+
+    /// Solves logically and, if the techniques stall, finishes the board by search.
+    pub fn solve_with_search(&self, board: &mut Board) -> Result<SolvedBy, SolveError> {
+        self.solve(board)?;
+        if board.is_solved() {
+            return Ok(SolvedBy::Logic);
+        }
+
+        let solution = search::search(board).ok_or(SolveError::NoSolution)?;
+        board.cells = solution.cells;
+        Ok(SolvedBy::Search)
+    }
+}
+
+/// Techniques run easiest first, so a puzzle is rated by the hardest step it actually needs.
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::new(vec![
+            Box::new(HiddenSingles),
+            Box::new(PointingPairs),
+            Box::new(BoxLineReductions),
+            Box::new(Nakeds::<2>),
+            Box::new(XWings::<2>),
+            Box::new(Hiddens::<2>),
+            Box::new(Skyscrapers),
+            Box::new(TwoStringKites),
+            Box::new(FinnedXWings::<2>),
+            Box::new(FrankenFish::<2>),
+            Box::new(Nakeds::<3>),
+            Box::new(MutantFish::<2>),
+            Box::new(XWings::<3>),
+            Box::new(Hiddens::<3>),
+            Box::new(FinnedXWings::<3>),
+            Box::new(FrankenFish::<3>),
+            Box::new(TurbotFish),
+            Box::new(YWings),
+            Box::new(MutantFish::<3>),
+            Box::new(XYZWings),
+            Box::new(WXYZWings),
+            Box::new(UniqueRectangles),
+            Box::new(AvoidableRectangles),
+            Box::new(HiddenUniqueRectangles),
+            Box::new(Nakeds::<4>),
+            Box::new(SueDeCoqs),
+            Box::new(XWings::<4>),
+            Box::new(Hiddens::<4>),
+            Box::new(FinnedXWings::<4>),
+            Box::new(FrankenFish::<4>),
+            Box::new(Bugs),
+            Box::new(MutantFish::<4>),
+            Box::new(XWings::<5>),
+            Box::new(XWings::<6>),
+            Box::new(AlignedExclusion::<2>),
+            Box::new(XWings::<7>),
+            Box::new(XCycles),
+            Box::new(XYChains::default()),
+            Box::new(Rectangles),
+            Box::new(SimpleColouring),
+            Box::new(Aics::default()),
+            Box::new(Medusa),
+            Box::new(AlsXz),
+            Box::new(AlignedExclusion::<3>),
+            Box::new(Nishio),
+            Box::new(AlsXyWings),
+            Box::new(DeathBlossoms),
+            Box::new(CellForcingChains),
+            Box::new(UnitForcingChains),
+            Box::new(DigitForcingChains),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_techniques_are_in_difficulty_order() {
+        let techniques = Solver::default().techniques;
+        for pair in techniques.windows(2) {
+            assert!(pair[0].difficulty() <= pair[1].difficulty(), "{} runs before {}", pair[0].name(), pair[1].name());
+        }
+    }
+}
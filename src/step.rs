@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use crate::{board::Cell, format::digit_char, misc::is_set};
+
+#[derive(Debug, Clone, Default)]
+pub struct Deduction {
+    pub cells: Vec<Cell>,
+    pub digits: u32,
+    pub placed: Vec<(Cell, u32)>,
+    pub eliminated: Vec<(Cell, u32)>,
+    /// Names the variant of the technique which fired, for techniques with several.
+    pub rule: Option<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub technique: &'static str,
+    pub difficulty: f32,
+    pub deduction: Deduction,
+}
+
+impl Deduction {
+    pub fn new(cells: Vec<Cell>, digits: u32) -> Self {
+        Deduction {
+            cells,
+            digits,
+            placed: vec![],
+            eliminated: vec![],
+            rule: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placed.is_empty() && self.eliminated.is_empty()
+    }
+}
+
+impl Step {
+    /// Displays the step with digits written as they are in a grid of `size`.
+    pub fn display(&self, size: usize) -> StepDisplay<'_> {
+        StepDisplay { step: self, size }
+    }
+}
+
+pub struct StepDisplay<'a> {
+    step: &'a Step,
+    size: usize,
+}
+
+impl Display for StepDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let deduction = &self.step.deduction;
+        write!(f, "{}", self.step.technique)?;
+        if let Some(rule) = deduction.rule {
+            write!(f, " ({rule})")?;
+        }
+        if deduction.digits != 0 {
+            write!(f, " {}", format_digits(deduction.digits, self.size))?;
+        }
+        if !deduction.cells.is_empty() {
+            write!(f, " in {}", format_cells(&deduction.cells))?;
+        }
+
+        let mut clauses = vec![];
+        let placed = group_by_digit(&deduction.placed, "in", self.size);
+        if !placed.is_empty() {
+            clauses.push(format!("places {}", placed.join(" and ")));
+        }
+        let removed = group_by_digit(&deduction.eliminated, "from", self.size);
+        if !removed.is_empty() {
+            clauses.push(format!("removes {}", removed.join(" and ")));
+        }
+
+        if !clauses.is_empty() {
+            write!(f, " {}", clauses.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn group_by_digit(candidates: &[(Cell, u32)], preposition: &str, size: usize) -> Vec<String> {
+    let mut digits: Vec<_> = candidates.iter().map(|(_, val)| *val).collect();
+    digits.sort_unstable();
+    digits.dedup();
+
+    digits
+        .into_iter()
+        .map(|d| {
+            let cells: Vec<_> = candidates.iter().filter(|(_, val)| *val == d).map(|(cell, _)| *cell).collect();
+            format!("{} {preposition} {}", digit_char(d, size), format_cells(&cells))
+        })
+        .collect()
+}
+
+pub fn format_digits(digits: u32, size: usize) -> String {
+    let digits: Vec<_> = (1..u32::BITS).filter(|d| is_set!(digits, d)).map(|d| digit_char(d, size).to_string()).collect();
+    format!("{{{}}}", digits.join(","))
+}
+
+pub fn format_cells(cells: &[Cell]) -> String {
+    cells.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
@@ -0,0 +1,196 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{cells, is_set, units, Unit},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct Skyscrapers;
+
+pub struct TwoStringKites;
+
+pub struct TurbotFish;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurbotKind {
+    /// Two conjugate pairs in parallel lines, with one end of each in a shared line.
+    Skyscraper,
+    /// A conjugate pair in a row and one in a column, with one end of each in a shared region.
+    Kite,
+    /// Any other pair of conjugate pairs, joined by an inner end of one seeing an inner end of the other.
+    Turbot,
+}
+
+/// Two conjugate pairs whose inner ends see each other, so one of the outer ends holds `val`.
+#[derive(Debug, Clone)]
+pub struct Turbot {
+    /// The chain in order: an outer end, its inner end, the other inner end, the other outer end.
+    pub cells: [Cell; 4],
+    pub val: u32,
+}
+
+macro_rules! impl_turbots {
+    ($technique:ident, $name:literal, $difficulty:literal, $kind:expr) => {
+        impl Technique for $technique {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board(board, $kind).iter().map(|turbot| turbot.deduction(board)).collect()
+            }
+        }
+    };
+}
+
+impl_turbots!(Skyscrapers, "Skyscraper", 3.4, TurbotKind::Skyscraper);
+impl_turbots!(TwoStringKites, "2-String Kite", 3.4, TurbotKind::Kite);
+impl_turbots!(TurbotFish, "Turbot Fish", 4.2, TurbotKind::Turbot);
+
+/// Finds pairs of conjugate pairs for one digit with an end of each seeing the other. Skyscrapers and kites are the
+/// shapes which are also sashimi fish, named here so they read as users expect. Only the pairs of links which can make
+/// a turbot of the given kind are searched.
+pub fn from_board(board: &Board, kind: TurbotKind) -> Rc<[Turbot]> {
+    let mut out = vec![];
+
+    #[allow(clippy::cast_possible_truncation)]
+    for val in (1..=board.size).map(|val| val as u32) {
+        let links = strong_links(board, val);
+
+        for (i, (x, first)) in links.iter().enumerate() {
+            for (y, second) in links[i + 1..].iter().filter(|(_, second)| can_make(kind, first, second)) {
+                for (outer_a, inner_a) in [(x.0, x.1), (x.1, x.0)] {
+                    for (outer_b, inner_b) in [(y.0, y.1), (y.1, y.0)] {
+                        let cells = [outer_a, inner_a, inner_b, outer_b];
+                        if cells.iter().zip(1..).any(|(a, j)| cells[j..].contains(a)) || !inner_a.can_see(board, &inner_b) {
+                            continue;
+                        }
+
+                        let turbot = Turbot { cells, val };
+                        if classify(board, first, second, cells) == kind && !turbot.deduction(board).eliminated.is_empty() {
+                            out.push(turbot);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Conjugate pairs for `val` along with the unit making them one.
+fn strong_links<'a>(board: &'a Board, val: u32) -> Vec<((Cell, Cell), Unit<'a>)> {
+    units(board)
+        .iter()
+        .filter_map(|unit| {
+            let holding: Vec<_> = unit.cells(board.size).into_iter().filter(|cell| is_set!(board[*cell], val)).collect();
+            match holding[..] {
+                [a, b] if board[a].count_ones() > 1 && board[b].count_ones() > 1 => Some(((a, b), *unit)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Whether links in these two units can form the kind at all, saving the search through both ends of each.
+fn can_make(kind: TurbotKind, first: &Unit, second: &Unit) -> bool {
+    matches!(
+        (kind, first, second),
+        (TurbotKind::Skyscraper, Unit::Row(_), Unit::Row(_))
+            | (TurbotKind::Skyscraper, Unit::Col(_), Unit::Col(_))
+            | (TurbotKind::Kite, Unit::Row(_), Unit::Col(_))
+            | (TurbotKind::Kite, Unit::Col(_), Unit::Row(_))
+            | (TurbotKind::Turbot, ..)
+    )
+}
+
+fn classify(board: &Board, first: &Unit, second: &Unit, [outer_a, inner_a, inner_b, outer_b]: [Cell; 4]) -> TurbotKind {
+    match (first, second) {
+        (Unit::Row(_), Unit::Row(_)) if inner_a.col == inner_b.col => TurbotKind::Skyscraper,
+        (Unit::Col(_), Unit::Col(_)) if inner_a.row == inner_b.row => TurbotKind::Skyscraper,
+        (Unit::Row(_), Unit::Col(_)) | (Unit::Col(_), Unit::Row(_)) => {
+            let shared = board.regions.iter().find(|region| region.contains(&inner_a) && region.contains(&inner_b));
+            match shared {
+                Some(region) if !region.contains(&outer_a) && !region.contains(&outer_b) => TurbotKind::Kite,
+                _ => TurbotKind::Turbot,
+            }
+        }
+        _ => TurbotKind::Turbot,
+    }
+}
+
+impl Turbot {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let [outer_a, .., outer_b] = self.cells;
+        let mut deduction = Deduction::new(self.cells.to_vec(), 1 << self.val);
+        deduction.eliminated.extend(
+            cells(board)
+                .iter()
+                .filter(|cell| !self.cells.contains(cell) && is_set!(board[**cell], self.val))
+                .filter(|cell| cell.can_see(board, &outer_a) && cell.can_see(board, &outer_b))
+                .map(|cell| (*cell, self.val)),
+        );
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Removes 1 from every cell of `line` outside `keep`, where `line` lists the cells of a row or column.
+    fn restrict(board: &mut Board, line: impl Iterator<Item = Cell>, keep: &[Cell]) {
+        for cell in line.filter(|cell| !keep.contains(cell)) {
+            board[cell] &= !(1 << 1);
+        }
+    }
+
+    fn eliminated(deductions: &[Deduction]) -> Vec<(Cell, u32)> {
+        let mut eliminated: Vec<_> = deductions.iter().flat_map(|deduction| deduction.eliminated.clone()).collect();
+        eliminated.sort_unstable();
+        eliminated.dedup();
+        eliminated
+    }
+
+    fn cells_of(coords: &[(usize, usize)]) -> Vec<(Cell, u32)> {
+        coords.iter().map(|(row, col)| (Cell { row: *row, col: *col }, 1)).collect()
+    }
+
+    #[test]
+    fn skyscraper_eliminates_what_sees_both_tops() {
+        // 1 is in r1 and r7 of the first column and in r3 and r7 of the fifth, sharing the seventh row
+        let mut board = Board::new(9);
+        restrict(&mut board, (0..9).map(|row| Cell { row, col: 0 }), &[Cell { row: 0, col: 0 }, Cell { row: 6, col: 0 }]);
+        restrict(&mut board, (0..9).map(|row| Cell { row, col: 4 }), &[Cell { row: 2, col: 4 }, Cell { row: 6, col: 4 }]);
+
+        assert_eq!(eliminated(&Skyscrapers.find(&board)), cells_of(&[(0, 3), (0, 5), (2, 1), (2, 2)]));
+    }
+
+    #[test]
+    fn two_string_kite_eliminates_where_the_strings_cross() {
+        // 1 is in c2 and c7 of the first row and in r3 and r8 of the first column, with r1c2 and r3c1 sharing a box
+        let mut board = Board::new(9);
+        restrict(&mut board, (0..9).map(|col| Cell { row: 0, col }), &[Cell { row: 0, col: 1 }, Cell { row: 0, col: 6 }]);
+        restrict(&mut board, (0..9).map(|row| Cell { row, col: 0 }), &[Cell { row: 2, col: 0 }, Cell { row: 7, col: 0 }]);
+
+        assert_eq!(eliminated(&TwoStringKites.find(&board)), cells_of(&[(7, 6)]));
+    }
+
+    #[test]
+    fn turbot_fish_joins_links_through_a_box() {
+        // 1 is in c1 and c7 of the first row and in c2 and c8 of the third, with both pairs of ends sharing a box
+        let mut board = Board::new(9);
+        restrict(&mut board, (0..9).map(|col| Cell { row: 0, col }), &[Cell { row: 0, col: 0 }, Cell { row: 0, col: 6 }]);
+        restrict(&mut board, (0..9).map(|col| Cell { row: 2, col }), &[Cell { row: 2, col: 1 }, Cell { row: 2, col: 7 }]);
+
+        assert_eq!(eliminated(&TurbotFish.find(&board)), cells_of(&[(1, 0), (1, 1), (1, 2), (1, 6), (1, 7), (1, 8)]));
+        assert!(Skyscrapers.find(&board).is_empty());
+    }
+}
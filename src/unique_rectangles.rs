@@ -0,0 +1,360 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{cells, combinations, digits, is_set, units},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct UniqueRectangles;
+
+pub struct HiddenUniqueRectangles;
+
+pub struct AvoidableRectangles;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RectangleType {
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Hidden,
+    AvoidableOne,
+    AvoidableTwo,
+}
+
+#[derive(Debug, Clone)]
+pub struct UniqueRectangle {
+    pub kind: RectangleType,
+    /// Corners in the order top left, top right, bottom left, bottom right.
+    pub cells: [Cell; 4],
+    pub vals: u32,
+    pub eliminated: Vec<(Cell, u32)>,
+}
+
+macro_rules! impl_rectangles {
+    ($technique:ident, $name:literal, $difficulty:literal, $from_board:ident) => {
+        impl Technique for $technique {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                $from_board(board).iter().map(UniqueRectangle::deduction).collect()
+            }
+
+            fn assumes_uniqueness(&self) -> bool {
+                true
+            }
+        }
+    };
+}
+
+impl_rectangles!(UniqueRectangles, "Unique Rectangle", 4.6, from_board);
+impl_rectangles!(HiddenUniqueRectangles, "Hidden Unique Rectangle", 4.8, from_board_hidden);
+impl_rectangles!(AvoidableRectangles, "Avoidable Rectangle", 4.7, from_board_avoidable);
+
+/// Finds unique rectangles of types 1 to 6: four unsolved corners in two rows, two columns and two regions, all holding
+/// the same two digits. Were only those digits left, they could be swapped to give a second solution, so the extra
+/// candidates in the roof corners cannot all be false.
+pub fn from_board(board: &Board) -> Rc<[UniqueRectangle]> {
+    let mut out = vec![];
+
+    for cells in rectangles(board) {
+        if cells.iter().any(|cell| board[*cell].count_ones() < 2) {
+            continue;
+        }
+        let common = cells.iter().fold(u32::MAX, |acc, cell| acc & board[*cell]);
+
+        for vals in digit_pairs(common) {
+            let (floor, roof): (Vec<_>, Vec<_>) = cells.iter().copied().partition(|cell| board[*cell] == vals);
+            let extra = |cell: Cell| board[cell] & !vals;
+            let rectangle = |kind, eliminated| UniqueRectangle { kind, cells, vals, eliminated };
+
+            match (floor.len(), &roof[..]) {
+                (3, [roof]) => out.push(rectangle(RectangleType::One, digits(vals).map(|val| (*roof, val)).collect())),
+                (2, [a, b]) if a.row == b.row || a.col == b.col => {
+                    if extra(*a) == extra(*b) && extra(*a).count_ones() == 1 {
+                        let val = extra(*a).trailing_zeros();
+                        out.push(rectangle(RectangleType::Two, seen_by_all(board, &[*a, *b], val)));
+                    }
+                    for unit in units(board).iter() {
+                        let unit_cells = unit.cells(board.size);
+                        if unit_cells.contains(a) && unit_cells.contains(b) {
+                            out.extend(type_three(board, &unit_cells, *a, *b, extra(*a) | extra(*b)).map(|eliminated| rectangle(RectangleType::Three, eliminated)));
+                            out.extend(type_four(board, &unit_cells, *a, *b, vals).map(|eliminated| rectangle(RectangleType::Four, eliminated)));
+                        }
+                    }
+                }
+                (2, [a, b]) => {
+                    if extra(*a) == extra(*b) && extra(*a).count_ones() == 1 {
+                        let val = extra(*a).trailing_zeros();
+                        out.push(rectangle(RectangleType::Five, seen_by_all(board, &[*a, *b], val)));
+                    }
+                    for val in digits(vals) {
+                        if is_locked(board, &cells, val) {
+                            out.push(rectangle(RectangleType::Six, vec![(*a, val), (*b, val)]));
+                        }
+                    }
+                }
+                (1, [a, b, c]) if extra(*a) == extra(*b) && extra(*a) == extra(*c) && extra(*a).count_ones() == 1 => {
+                    let val = extra(*a).trailing_zeros();
+                    out.push(rectangle(RectangleType::Five, seen_by_all(board, &[*a, *b, *c], val)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    out.retain(|rectangle| !rectangle.eliminated.is_empty());
+    out.into()
+}
+
+/// Finds hidden unique rectangles: a bivalue corner whose opposite corner is the only place in its row and column of
+/// the rectangle for one digit, which rules out the other digit there.
+pub fn from_board_hidden(board: &Board) -> Rc<[UniqueRectangle]> {
+    let mut out = vec![];
+
+    for cells in rectangles(board) {
+        if cells.iter().any(|cell| board[*cell].count_ones() < 2) {
+            continue;
+        }
+        let common = cells.iter().fold(u32::MAX, |acc, cell| acc & board[*cell]);
+
+        for vals in digit_pairs(common) {
+            for (floor, opposite) in [(0, 3), (3, 0), (1, 2), (2, 1)] {
+                if board[cells[floor]] != vals {
+                    continue;
+                }
+                let target = cells[opposite];
+                let row_partner = cells[opposite ^ 1];
+                let col_partner = cells[opposite ^ 2];
+
+                for val in digits(vals) {
+                    let row_locked = (0..board.size).all(|col| col == target.col || col == row_partner.col || !is_set!(board.cells[target.row][col], val));
+                    let col_locked = (0..board.size).all(|row| row == target.row || row == col_partner.row || !is_set!(board.cells[row][target.col], val));
+                    if row_locked && col_locked {
+                        out.push(UniqueRectangle {
+                            kind: RectangleType::Hidden,
+                            cells,
+                            vals,
+                            eliminated: vec![(target, (vals & !(1 << val)).trailing_zeros())],
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    out.into()
+}
+
+/// Finds avoidable rectangles, where corners solved during the solve rather than given would form a swappable pattern
+/// with the remaining corners. Without the givens no corner can be told apart from one, so nothing is found.
+pub fn from_board_avoidable(board: &Board) -> Rc<[UniqueRectangle]> {
+    let mut out = vec![];
+    let Some(givens) = &board.givens else {
+        return out.into();
+    };
+    let solved = |cell: &Cell| board[*cell].count_ones() == 1 && givens[cell.row][cell.col] == 0;
+
+    for cells in rectangles(board) {
+        if cells.iter().any(|cell| board[*cell].count_ones() == 1 && !solved(cell)) {
+            continue;
+        }
+        let unsolved: Vec<_> = (0..4).filter(|i| !solved(&cells[*i])).collect();
+
+        match unsolved[..] {
+            [i] => {
+                // The corner opposite the unsolved one may not be repeated there
+                let (opposite, sides) = (board[cells[3 - i]], [board[cells[i ^ 1]], board[cells[i ^ 2]]]);
+                if sides[0] == sides[1] && opposite != sides[0] && board[cells[i]] & opposite != 0 {
+                    out.push(UniqueRectangle {
+                        kind: RectangleType::AvoidableOne,
+                        cells,
+                        vals: opposite | sides[0],
+                        eliminated: vec![(cells[i], opposite.trailing_zeros())],
+                    });
+                }
+            }
+            [i, j] if i ^ j != 3 => {
+                // Each unsolved corner must avoid the digit solved diagonally opposite it unless the other takes the shared extra
+                let (a, b) = (board[cells[3 - i]], board[cells[3 - j]]);
+                let (extra_i, extra_j) = (board[cells[i]] & !a, board[cells[j]] & !b);
+                if a != b && board[cells[i]] & a != 0 && board[cells[j]] & b != 0 && extra_i == extra_j && extra_i.count_ones() == 1 {
+                    let val = extra_i.trailing_zeros();
+                    out.push(UniqueRectangle {
+                        kind: RectangleType::AvoidableTwo,
+                        cells,
+                        vals: a | b,
+                        eliminated: seen_by_all(board, &[cells[i], cells[j]], val),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    out.retain(|rectangle| !rectangle.eliminated.is_empty());
+    out.into()
+}
+
+/// Every rectangle of cells spanning exactly two regions, as only those can have their digits swapped.
+fn rectangles(board: &Board) -> Vec<[Cell; 4]> {
+    let size = board.size;
+    let region_of: Vec<Vec<_>> = (0..size)
+        .map(|row| (0..size).map(|col| board.regions.iter().position(|region| region.contains(&Cell { row, col }))).collect())
+        .collect();
+
+    let mut out = vec![];
+    for top in 0..size {
+        for bottom in top + 1..size {
+            for left in 0..size {
+                for right in left + 1..size {
+                    let cells = [
+                        Cell { row: top, col: left },
+                        Cell { row: top, col: right },
+                        Cell { row: bottom, col: left },
+                        Cell { row: bottom, col: right },
+                    ];
+                    let mut regions: Vec<_> = cells.iter().map(|cell| region_of[cell.row][cell.col]).collect();
+                    regions.sort_unstable();
+                    regions.dedup();
+                    if regions.len() == 2 {
+                        out.push(cells);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn digit_pairs(mask: u32) -> Vec<u32> {
+    let vals: Vec<_> = digits(mask).collect();
+    combinations(&vals, 2).iter().map(|pair| 1 << pair[0] | 1 << pair[1]).collect()
+}
+
+/// Candidates `val` outside `cells` which see all of them.
+fn seen_by_all(board: &Board, cells_seen: &[Cell], val: u32) -> Vec<(Cell, u32)> {
+    cells(board)
+        .iter()
+        .filter(|cell| !cells_seen.contains(cell) && is_set!(board[**cell], val))
+        .filter(|cell| cells_seen.iter().all(|seen| seen.can_see(board, cell)))
+        .map(|cell| (*cell, val))
+        .collect()
+}
+
+/// Type 3: the roof's extra digits act as one cell which, with other cells of a shared unit, forms a naked subset.
+fn type_three(board: &Board, unit_cells: &[Cell], a: Cell, b: Cell, extras: u32) -> Option<Vec<(Cell, u32)>> {
+    let others: Vec<_> = unit_cells.iter().copied().filter(|cell| *cell != a && *cell != b && board[*cell].count_ones() > 1).collect();
+
+    for size in 1..others.len().min(4) {
+        for subset in combinations(&others, size) {
+            let vals = subset.iter().fold(extras, |acc, cell| acc | board[*cell]);
+            if vals.count_ones() as usize == size + 1 {
+                let eliminated: Vec<_> = others
+                    .iter()
+                    .filter(|cell| !subset.contains(cell))
+                    .flat_map(|cell| digits(vals & board[*cell]).map(|val| (*cell, val)))
+                    .collect();
+                if !eliminated.is_empty() {
+                    return Some(eliminated);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Type 4: one of the digits is confined to the roof within a shared unit, so the other cannot be in the roof.
+fn type_four(board: &Board, unit_cells: &[Cell], a: Cell, b: Cell, vals: u32) -> Option<Vec<(Cell, u32)>> {
+    digits(vals)
+        .find(|val| unit_cells.iter().all(|cell| *cell == a || *cell == b || !is_set!(board[*cell], val)))
+        .map(|val| {
+            let other = (vals & !(1 << val)).trailing_zeros();
+            vec![(a, other), (b, other)]
+        })
+}
+
+/// Whether `val` appears in the rectangle's rows and columns only at its corners.
+fn is_locked(board: &Board, cells: &[Cell; 4], val: u32) -> bool {
+    let (rows, cols) = ([cells[0].row, cells[3].row], [cells[0].col, cells[3].col]);
+    (0..board.size).all(|i| rows.iter().all(|row| cols.contains(&i) || !is_set!(board.cells[*row][i], val)) && cols.iter().all(|col| rows.contains(&i) || !is_set!(board.cells[i][*col], val)))
+}
+
+impl RectangleType {
+    /// The type shown after the technique name, if the name alone does not say it.
+    pub fn description(self) -> Option<&'static str> {
+        match self {
+            RectangleType::One | RectangleType::AvoidableOne => Some("type 1"),
+            RectangleType::Two | RectangleType::AvoidableTwo => Some("type 2"),
+            RectangleType::Three => Some("type 3"),
+            RectangleType::Four => Some("type 4"),
+            RectangleType::Five => Some("type 5"),
+            RectangleType::Six => Some("type 6"),
+            RectangleType::Hidden => None,
+        }
+    }
+}
+
+impl UniqueRectangle {
+    pub fn deduction(&self) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.to_vec(), self.vals);
+        deduction.rule = self.kind.description();
+        deduction.eliminated.clone_from(&self.eliminated);
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+
+    #[test]
+    fn avoidable_rectangles_need_the_givens() {
+        // Three corners solved during the solve as 1 2 / 2 1, which the last corner would complete
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1;
+        board[Cell { row: 0, col: 3 }] = 1 << 2;
+        board[Cell { row: 1, col: 0 }] = 1 << 2;
+        board[Cell { row: 1, col: 3 }] = 1 << 1 | 1 << 5;
+        assert!(from_board_avoidable(&board).is_empty());
+
+        board.givens = Some(vec![vec![0; 9]; 9]);
+        let eliminated: Vec<_> = from_board_avoidable(&board).iter().flat_map(|rectangle| rectangle.deduction().eliminated).collect();
+        assert_eq!(eliminated, [(Cell { row: 1, col: 3 }, 1)]);
+    }
+
+    #[test]
+    fn avoidable_rectangle_is_the_next_default_step() {
+        // Corners solved as 1 2 / 2 rather than given, and the band's last 2 placed so nothing easier fires, so r2c4 must
+        // avoid the 1 that would complete a swappable rectangle
+        let mut board = Board::from_givens(&vec![vec![0; 9]; 9]).unwrap();
+        board.unique = true;
+        for (val, row, col) in [(1, 0, 0), (2, 0, 3), (2, 1, 0), (2, 2, 6)] {
+            board.place_digit(val, Cell { row, col }).unwrap();
+        }
+        board[Cell { row: 1, col: 3 }] = 1 << 1 | 1 << 5;
+
+        let solver = Solver::default();
+        let (technique, deductions) = solver
+            .techniques
+            .iter()
+            .map(|technique| (technique.name(), technique.find(&board)))
+            .find(|(_, deductions)| !deductions.is_empty())
+            .unwrap();
+        assert_eq!(technique, "Avoidable Rectangle");
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].rule, Some("type 1"));
+        assert_eq!(deductions[0].eliminated, [(Cell { row: 1, col: 3 }, 1)]);
+    }
+}
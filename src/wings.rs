@@ -0,0 +1,164 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{bivalue_cells, cells, is_set},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct XYZWings;
+
+pub struct WXYZWings;
+
+#[derive(Debug, Clone)]
+pub struct Wing {
+    pub cells: Vec<Cell>,
+    pub vals: u32,
+    /// The only digit not confined to cells which all see each other, so it must be placed in one of its wing cells.
+    pub val: u32,
+}
+
+impl Technique for XYZWings {
+    fn name(&self) -> &'static str {
+        "XYZ-Wing"
+    }
+
+    fn difficulty(&self) -> f32 {
+        4.4
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board3(board).iter().map(|wing| wing.deduction(board)).collect()
+    }
+}
+
+impl Technique for WXYZWings {
+    fn name(&self) -> &'static str {
+        "WXYZ-Wing"
+    }
+
+    fn difficulty(&self) -> f32 {
+        4.6
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board4(board).iter().map(|wing| wing.deduction(board)).collect()
+    }
+}
+
+/// Finds XYZ-Wings: a pivot with three candidates which sees two bivalue pincers, each sharing the digit `val` and
+/// one other digit with the pivot.
+pub fn from_board3(board: &Board) -> Rc<[Wing]> {
+    let cells = cells(board);
+    let pincers = bivalue_cells(board);
+
+    cells
+        .iter()
+        .copied()
+        .filter(|cell| board[*cell].count_ones() == 3)
+        .flat_map(|pivot| {
+            let useful: Vec<_> = pincers.iter().copied().filter(|pincer| board[*pincer] & !board[pivot] == 0 && pivot.can_see(board, pincer)).collect();
+            useful
+                .iter()
+                .zip(1..)
+                .flat_map(|(a, i)| useful[i..].iter().map(move |b| (*a, *b)))
+                .filter(|(a, b)| board[*a] != board[*b])
+                .map(|(a, b)| Wing {
+                    cells: vec![pivot, a, b],
+                    vals: board[pivot],
+                    val: (board[a] & board[b]).trailing_zeros(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Finds WXYZ-Wings: four cells holding four digits between them, where every digit but `val` is restricted to cells
+/// that all see each other.
+pub fn from_board4(board: &Board) -> Rc<[Wing]> {
+    let useful: Vec<_> = cells(board).iter().copied().filter(|cell| (2..=4).contains(&board[*cell].count_ones())).collect();
+
+    let mut found = vec![];
+    combine_cells(board, &useful, &mut vec![], 0, &mut found);
+    found.into()
+}
+
+fn combine_cells(board: &Board, useful: &[Cell], chosen: &mut Vec<Cell>, vals: u32, found: &mut Vec<Wing>) {
+    if chosen.len() == 4 {
+        if vals.count_ones() == 4 {
+            let unrestricted: Vec<_> = (1..=board.size).filter(|d| is_set!(vals, d) && !is_restricted(board, chosen, *d)).collect();
+            if let [val] = unrestricted[..] {
+                #[allow(clippy::cast_possible_truncation)]
+                found.push(Wing {
+                    cells: chosen.clone(),
+                    vals,
+                    val: val as u32,
+                });
+            }
+        }
+        return;
+    }
+
+    for (i, cell) in useful.iter().enumerate() {
+        let vals = vals | board[*cell];
+        if vals.count_ones() <= 4 {
+            chosen.push(*cell);
+            combine_cells(board, &useful[i + 1..], chosen, vals, found);
+            chosen.pop();
+        }
+    }
+}
+
+fn is_restricted(board: &Board, cells: &[Cell], val: usize) -> bool {
+    let holding: Vec<_> = cells.iter().filter(|cell| is_set!(board[**cell], val)).collect();
+    holding.iter().zip(1..).all(|(a, i)| holding[i..].iter().all(|b| a.can_see(board, b)))
+}
+
+impl Wing {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let holding: Vec<_> = self.cells.iter().filter(|cell| is_set!(board[**cell], self.val)).collect();
+
+        let mut deduction = Deduction::new(self.cells.clone(), self.vals);
+        deduction.eliminated.extend(
+            cells(board)
+                .iter()
+                .filter(|cell| !self.cells.contains(cell) && is_set!(board[**cell], self.val))
+                .filter(|cell| holding.iter().all(|wing_cell| wing_cell.can_see(board, cell)))
+                .map(|cell| (*cell, self.val)),
+        );
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xyz_wing_eliminates_from_cells_seeing_all_three() {
+        // Pivot r1c1 {1,2,3} with pincers r1c5 {1,3} in its row and r2c2 {2,3} in its box
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2 | 1 << 3;
+        board[Cell { row: 0, col: 4 }] = 1 << 1 | 1 << 3;
+        board[Cell { row: 1, col: 1 }] = 1 << 2 | 1 << 3;
+
+        let deductions = XYZWings.find(&board);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].eliminated, [(Cell { row: 0, col: 1 }, 3), (Cell { row: 0, col: 2 }, 3)]);
+    }
+
+    #[test]
+    fn wxyz_wing_eliminates_from_cells_seeing_all_four() {
+        // Pivot r1c1 {1,2,3,4} with pincers r1c5 {1,4}, r1c9 {3,4} and r2c2 {2,4}, which only share 4
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4;
+        board[Cell { row: 0, col: 4 }] = 1 << 1 | 1 << 4;
+        board[Cell { row: 0, col: 8 }] = 1 << 3 | 1 << 4;
+        board[Cell { row: 1, col: 1 }] = 1 << 2 | 1 << 4;
+
+        let deductions = WXYZWings.find(&board);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].eliminated, [(Cell { row: 0, col: 1 }, 4), (Cell { row: 0, col: 2 }, 4)]);
+    }
+}
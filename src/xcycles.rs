@@ -0,0 +1,177 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{cells, conjugate_pairs, is_set},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct XCycles;
+
+// Loops are built from strong links joined by weak ones, so this caps a loop at twice as many cells
+const MAX_STRONG_LINKS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleKind {
+    /// Strong and weak links alternate all the way round, so every link can be cleared outside the loop.
+    Continuous,
+    /// Two strong links meet at the first cell, which must hold the digit.
+    Strong,
+    /// Two weak links meet at the first cell, which cannot hold the digit.
+    Weak,
+}
+
+#[derive(Debug, Clone)]
+pub struct XCycle {
+    pub kind: CycleKind,
+    /// The loop in order, starting at the discontinuity if there is one.
+    pub cells: Vec<Cell>,
+    pub val: u32,
+}
+
+impl Technique for XCycles {
+    fn name(&self) -> &'static str {
+        "X-Cycle"
+    }
+
+    fn difficulty(&self) -> f32 {
+        6.5
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board).iter().map(|cycle| cycle.deduction(board)).collect()
+    }
+}
+
+/// Finds X-Cycles for every digit, shortest first, keeping only the shortest loop for each discontinuity.
+pub fn from_board(board: &Board) -> Rc<[XCycle]> {
+    let mut cycles = vec![];
+
+    #[allow(clippy::cast_possible_truncation)]
+    for val in (1..=board.size).map(|val| val as u32) {
+        let links: Vec<_> = conjugate_pairs(board, val).into_iter().flat_map(|(a, b)| [(a, b), (b, a)]).collect();
+        let mut found = vec![];
+        for link in &links {
+            extend_chain(board, &links, &mut vec![*link], val, &mut found);
+        }
+
+        found.sort_by_key(|cycle: &XCycle| cycle.cells.len());
+        let mut discontinuities = vec![];
+        for cycle in found {
+            if cycle.kind != CycleKind::Continuous {
+                if discontinuities.contains(&cycle.cells[0]) {
+                    continue;
+                }
+                discontinuities.push(cycle.cells[0]);
+            }
+            cycles.push(cycle);
+        }
+    }
+
+    cycles.sort_by_key(|cycle| cycle.cells.len());
+    cycles.into()
+}
+
+fn extend_chain(board: &Board, links: &[(Cell, Cell)], chain: &mut Vec<(Cell, Cell)>, val: u32, found: &mut Vec<XCycle>) {
+    let start = chain[0].0;
+    let end = chain[chain.len() - 1].1;
+    let chain_cells: Vec<_> = chain.iter().flat_map(|(a, b)| [*a, *b]).collect();
+
+    if chain.len() > 1 {
+        if end == start {
+            found.push(XCycle {
+                kind: CycleKind::Strong,
+                cells: chain_cells[..chain_cells.len() - 1].to_vec(),
+                val,
+            });
+            return;
+        }
+
+        // Each continuous loop is reported once, from its smallest cell and in the direction leaving it by a strong link
+        if end.can_see(board, &start) && chain_cells[1..].iter().all(|cell| *cell > start) {
+            found.push(XCycle {
+                kind: CycleKind::Continuous,
+                cells: chain_cells.clone(),
+                val,
+            });
+        }
+
+        for cell in cells(board).iter() {
+            if !chain_cells.contains(cell) && is_set!(board[*cell], val) && cell.can_see(board, &start) && cell.can_see(board, &end) {
+                let mut cells = vec![*cell];
+                cells.extend(&chain_cells);
+                found.push(XCycle { kind: CycleKind::Weak, cells, val });
+            }
+        }
+    }
+
+    if chain.len() == MAX_STRONG_LINKS {
+        return;
+    }
+
+    for (a, b) in links {
+        if *a != end && end.can_see(board, a) && !chain_cells.contains(a) && (!chain_cells.contains(b) || *b == start) {
+            chain.push((*a, *b));
+            extend_chain(board, links, chain, val, found);
+            chain.pop();
+        }
+    }
+}
+
+impl XCycle {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let mut deduction = Deduction::new(self.cells.clone(), 1 << self.val);
+        match self.kind {
+            CycleKind::Strong => deduction.placed.push((self.cells[0], self.val)),
+            CycleKind::Weak => deduction.eliminated.push((self.cells[0], self.val)),
+            CycleKind::Continuous => {
+                // Exactly one end of each link holds the digit
+                for (a, b) in self.cells.iter().zip(self.cells[1..].iter().chain([&self.cells[0]])) {
+                    deduction.eliminated.extend(
+                        cells(board)
+                            .iter()
+                            .filter(|cell| !self.cells.contains(cell) && is_set!(board[**cell], self.val))
+                            .filter(|cell| cell.can_see(board, a) && cell.can_see(board, b))
+                            .map(|cell| (*cell, self.val)),
+                    );
+                }
+            }
+        }
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_x_cycle_clears_every_link() {
+        // 1 is held by r1c1 and r1c5 in the first row and by r3c1 and r3c6 in the third, making the loop
+        // r1c1 = r1c5 - r3c6 = r3c1 - r1c1
+        let mut board = Board::new(9);
+        for col in 1..9 {
+            if col != 4 {
+                board[Cell { row: 0, col }] &= !(1 << 1);
+            }
+            if col != 5 {
+                board[Cell { row: 2, col }] &= !(1 << 1);
+            }
+        }
+
+        let cycles = from_board(&board);
+        let continuous: Vec<_> = cycles.iter().filter(|cycle| cycle.kind == CycleKind::Continuous).collect();
+        assert_eq!(continuous.len(), 1);
+        let mut eliminated = continuous[0].deduction(&board).eliminated;
+        eliminated.sort_unstable();
+
+        // Cells seeing both r1c5 and r3c6, then both r3c1 and r1c1
+        let box_row = (3..6).map(|col| Cell { row: 1, col });
+        let column = (1..9).filter(|row| *row != 2).map(|row| Cell { row, col: 0 });
+        let box_rest = (1..3).map(|col| Cell { row: 1, col });
+        let mut expected: Vec<_> = box_row.chain(column).chain(box_rest).map(|cell| (cell, 1)).collect();
+        expected.sort_unstable();
+        assert_eq!(eliminated, expected);
+    }
+}
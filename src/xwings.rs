@@ -2,142 +2,441 @@ use std::rc::Rc;
 
 use crate::{
     board::{Board, Cell},
-    defaults::default_cell,
-    misc::is_set,
-    SIZE,
+    misc::{combinations, is_set, units, Unit},
+    solver::Technique,
+    step::Deduction,
 };
 
-#[derive(Debug, Clone, Copy)]
+pub struct XWings<const S: usize>;
+
+pub struct FinnedXWings<const S: usize>;
+
+pub struct FrankenFish<const S: usize>;
+
+pub struct MutantFish<const S: usize>;
+
+#[derive(Debug, Clone)]
 pub struct XWing<const S: usize> {
     pub clear_rows: bool,
     pub rows: [usize; S],
     pub cols: [usize; S],
-    pub val: u16,
+    pub val: u32,
+    /// Candidates in the base lines outside the cover lines, all of which an elimination must see.
+    pub fins: Vec<Cell>,
+    /// Set when a finned fish has a base and cover line crossing without the digit.
+    pub sashimi: bool,
 }
 
-macro_rules! get_values {
-    ($board:ident, $xwing:ident) => {
-        $xwing
-            .rows
-            .iter()
-            .flat_map(|row| $xwing.cols.iter().map(|col| Cell { row: *row, col: *col }))
-            .map(|cell| $board[cell])
-            .collect::<Rc<[_]>>()
+/// A fish whose base or cover sets include regions. Franken fish pair rows with columns and mix in regions on either
+/// side, while mutant fish use any mix of units.
+#[derive(Debug, Clone)]
+pub struct ComplexFish {
+    pub base: Vec<Cell>,
+    pub targets: Vec<Cell>,
+    pub val: u32,
+}
+
+macro_rules! impl_xwings {
+    ($size:literal, $name:literal, $difficulty:literal, $finned_name:literal, $finned_difficulty:literal) => {
+        impl Technique for XWings<$size> {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board::<$size>(board, false).iter().map(|xwing| xwing.deduction(board)).collect()
+            }
+        }
+
+        impl Technique for FinnedXWings<$size> {
+            fn name(&self) -> &'static str {
+                $finned_name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $finned_difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board::<$size>(board, true).iter().map(|xwing| xwing.deduction(board)).collect()
+            }
+        }
     };
 }
 
-macro_rules! is_valid {
-    ($board:ident, $xwing:ident) => {
-        if $xwing.clear_rows {
-            (0..SIZE).all(|row| {
-                $xwing
-                    .cols
-                    .iter()
-                    .all(|col| $xwing.rows.contains(&row) || !is_set!($board.get_cell_coords(row, *col).unwrap(), $xwing.val))
-            })
-        } else {
-            (0..SIZE).all(|col| {
-                $xwing
-                    .rows
-                    .iter()
-                    .all(|row| $xwing.cols.contains(&col) || !is_set!($board.get_cell_coords(*row, col).unwrap(), $xwing.val))
-            })
+impl_xwings!(2, "X-Wing", 3.2, "Finned X-Wing", 3.4);
+impl_xwings!(3, "Swordfish", 3.8, "Finned Swordfish", 4.0);
+impl_xwings!(4, "Jellyfish", 5.2, "Finned Jellyfish", 5.4);
+impl_xwings!(5, "Squirmbag", 5.6, "Finned Squirmbag", 5.8);
+impl_xwings!(6, "Whale", 6.0, "Finned Whale", 6.2);
+impl_xwings!(7, "Leviathan", 6.4, "Finned Leviathan", 6.6);
+
+macro_rules! impl_complex_fish {
+    ($size:literal, $franken_name:literal, $franken_difficulty:literal, $mutant_name:literal, $mutant_difficulty:literal) => {
+        impl Technique for FrankenFish<$size> {
+            fn name(&self) -> &'static str {
+                $franken_name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $franken_difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board_complex::<$size>(board, false).iter().map(ComplexFish::deduction).collect()
+            }
+        }
+
+        impl Technique for MutantFish<$size> {
+            fn name(&self) -> &'static str {
+                $mutant_name
+            }
+
+            fn difficulty(&self) -> f32 {
+                $mutant_difficulty
+            }
+
+            fn find(&self, board: &Board) -> Vec<Deduction> {
+                from_board_complex::<$size>(board, true).iter().map(ComplexFish::deduction).collect()
+            }
         }
     };
 }
 
-pub fn from_board2(board: &Board) -> Rc<[XWing<2>]> {
-    let pairs: Vec<_> = (0..(SIZE - 1)).flat_map(|a| ((a + 1)..SIZE).map(move |b| [a, b])).collect();
-
-    pairs[..]
-        .iter()
-        .flat_map(|a| pairs[..].iter().map(move |b| (a, b)))
-        .flat_map(|(rows, cols)| {
-            [true, false].iter().map(|clear_rows| XWing {
-                clear_rows: *clear_rows,
-                rows: *rows,
-                cols: *cols,
-                val: 0,
-            })
-        })
-        .map(|xwing| (xwing, get_values!(board, xwing)))
-        .filter(|(_xwing, vals)| vals.iter().all(|v| v.count_ones() > 1))
-        .flat_map(|(xwing, vals)| {
-            let v = vals.iter().fold(default_cell(), |acc, val| acc & val);
-            (1..=SIZE).filter_map(move |d| {
-                if is_set!(v, d) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    Some(XWing {
-                        clear_rows: xwing.clear_rows,
-                        rows: xwing.rows,
-                        cols: xwing.cols,
-                        val: d as u16,
-                    })
-                } else {
-                    None
+impl_complex_fish!(2, "Franken X-Wing", 3.4, "Mutant X-Wing", 3.6);
+impl_complex_fish!(3, "Franken Swordfish", 4.0, "Mutant Swordfish", 4.2);
+impl_complex_fish!(4, "Franken Jellyfish", 5.4, "Mutant Jellyfish", 5.6);
+
+/// Finds fish of size `S` by combining base lines which hold `val`, abandoning a combination as soon as the positions
+/// they cover could no longer be split into `S` cover lines and fins. A basic fish has no fins, while a finned fish
+/// may leave up to a box width of positions outside its cover lines.
+///
+/// Fish larger than half the grid are skipped, as the remaining lines then form a smaller fish with the same effect.
+pub fn from_board<const S: usize>(board: &Board, finned: bool) -> Rc<[XWing<S>]> {
+    let size = board.size;
+    if 2 * S > size {
+        return Rc::new([]);
+    }
+    let max_positions = if finned { S + board.box_width.max(board.box_height) } else { S };
+    let mut out = vec![];
+
+    for clear_rows in [true, false] {
+        #[allow(clippy::cast_possible_truncation)]
+        for val in (1..=size).map(|val| val as u32) {
+            let cell = |line: usize, pos: usize| if clear_rows { Cell { row: pos, col: line } } else { Cell { row: line, col: pos } };
+            let lines: Vec<_> = (0..size)
+                .map(|line| (line, (0..size).filter(|pos| is_set!(board[cell(line, *pos)], val)).fold(0u32, |acc, pos| acc | 1 << pos)))
+                .filter(|(_, positions)| (2..=max_positions).contains(&(positions.count_ones() as usize)))
+                .collect();
+
+            let mut found = vec![];
+            combine_lines::<S>(&lines, &mut vec![], 0, max_positions, &mut found);
+
+            for (base, union) in found {
+                let positions: Vec<_> = (0..size).filter(|pos| union & 1 << pos > 0).collect();
+                if positions.len() == S {
+                    if !finned {
+                        out.push(XWing::new(clear_rows, base, positions[..].try_into().unwrap(), val, vec![], false));
+                    }
+                    continue;
+                }
+                if !finned {
+                    continue;
                 }
-            })
-        })
-        .filter(|xwing| is_valid!(board, xwing))
-        .collect()
+
+                for cover in combinations(&positions, S) {
+                    let cover: [usize; S] = cover[..].try_into().unwrap();
+                    if !base.iter().all(|line| cover.iter().any(|pos| is_set!(board[cell(*line, *pos)], val))) {
+                        continue;
+                    }
+                    let fins = base
+                        .iter()
+                        .flat_map(|line| positions.iter().filter(|pos| !cover.contains(pos)).map(|pos| cell(*line, *pos)))
+                        .filter(|fin| is_set!(board[*fin], val))
+                        .collect();
+                    let sashimi = base.iter().any(|line| cover.iter().any(|pos| !is_set!(board[cell(*line, *pos)], val)));
+
+                    let xwing = XWing::new(clear_rows, base, cover, val, fins, sashimi);
+                    if !xwing.deduction(board).is_empty() {
+                        out.push(xwing);
+                    }
+                }
+            }
+        }
+    }
+
+    out.into()
 }
 
-pub fn from_board3(board: &Board) -> Rc<[XWing<3>]> {
-    let unit_groups: Vec<_> = (0..(SIZE - 2)).flat_map(|a| ((a + 1)..(SIZE - 1)).flat_map(move |b| ((b + 1)..SIZE).map(move |c| [a, b, c]))).collect();
-
-    unit_groups[..]
-        .iter()
-        .flat_map(|a| unit_groups[..].iter().map(move |b| (a, b)))
-        .flat_map(|(rows, cols)| {
-            [true, false].iter().map(|clear_rows| XWing {
-                clear_rows: *clear_rows,
-                rows: *rows,
-                cols: *cols,
-                val: 0,
-            })
-        })
-        .flat_map(|xwing| {
-            (1..=SIZE).map(move |d| {
-                #[allow(clippy::cast_possible_truncation)]
-                XWing {
-                    clear_rows: xwing.clear_rows,
-                    rows: xwing.rows,
-                    cols: xwing.cols,
-                    val: d as u16,
+fn combine_lines<const S: usize>(lines: &[(usize, u32)], chosen: &mut Vec<usize>, union: u32, max_positions: usize, found: &mut Vec<([usize; S], u32)>) {
+    if chosen.len() == S {
+        found.push((chosen[..].try_into().unwrap(), union));
+        return;
+    }
+
+    for (i, (line, positions)) in lines.iter().enumerate() {
+        let union = union | positions;
+        if union.count_ones() as usize <= max_positions {
+            chosen.push(*line);
+            combine_lines::<S>(&lines[i + 1..], chosen, union, max_positions, found);
+            chosen.pop();
+        }
+    }
+}
+
+/// Finds Franken or mutant fish of size `S`: `S` base units with no candidate of `val` in common, whose candidates
+/// all lie in `S` cover units. Each base unit then holds `val` in a different cover unit, so the cover units cannot
+/// hold it anywhere else. Fish using only rows and columns are left to `from_board`.
+pub fn from_board_complex<const S: usize>(board: &Board, mutant: bool) -> Rc<[ComplexFish]> {
+    let size = board.size;
+    let units = units(board);
+    let mut out = vec![];
+
+    #[allow(clippy::cast_possible_truncation)]
+    for val in (1..=size).map(|val| val as u32) {
+        let unit_cells: Vec<Vec<Cell>> = units
+            .iter()
+            .map(|unit| unit.cells(size).into_iter().filter(|cell| is_set!(board[*cell], val) && board[*cell].count_ones() > 1).collect())
+            .collect();
+
+        let disjoint: Vec<Vec<bool>> = unit_cells.iter().map(|a| unit_cells.iter().map(|b| a.iter().all(|cell| !b.contains(cell))).collect()).collect();
+        let mut bases = vec![];
+        combine_units(S, &unit_cells, &disjoint, 0, &mut vec![], &mut bases);
+
+        for base in bases {
+            let base_cells: Vec<_> = base.iter().flat_map(|i| unit_cells[*i].iter().copied()).collect();
+            let mut covers = vec![];
+            cover_units(S, &unit_cells, &base, &base_cells, &mut vec![], &mut covers);
+
+            for cover in covers {
+                let base_units: Vec<_> = base.iter().map(|i| units[*i]).collect();
+                let cover_units: Vec<_> = cover.iter().map(|i| units[*i]).collect();
+                if classify(&base_units, &cover_units) != Some(mutant) {
+                    continue;
+                }
+
+                let mut targets: Vec<_> = cover.iter().flat_map(|i| unit_cells[*i].iter().copied()).filter(|cell| !base_cells.contains(cell)).collect();
+                targets.sort_unstable();
+                targets.dedup();
+                if !targets.is_empty() {
+                    out.push(ComplexFish {
+                        base: base_cells.clone(),
+                        targets,
+                        val,
+                    });
                 }
-            })
-        })
-        .filter(|xwing| is_valid!(board, xwing))
-        .collect()
+            }
+        }
+    }
+
+    out.into()
+}
+
+fn combine_units(size: usize, unit_cells: &[Vec<Cell>], disjoint: &[Vec<bool>], from: usize, chosen: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    if chosen.len() == size {
+        found.push(chosen.clone());
+        return;
+    }
+
+    for i in from..unit_cells.len() {
+        if unit_cells[i].len() >= 2 && chosen.iter().all(|j| disjoint[*j][i]) {
+            chosen.push(i);
+            // Adding base units only makes covering harder, so give up on any partial base that already needs too many
+            let base_cells: Vec<_> = chosen.iter().flat_map(|j| unit_cells[*j].iter().copied()).collect();
+            if can_cover(size, unit_cells, chosen, &base_cells, &mut vec![]) {
+                combine_units(size, unit_cells, disjoint, i + 1, chosen, found);
+            }
+            chosen.pop();
+        }
+    }
+}
+
+fn cover_units(size: usize, unit_cells: &[Vec<Cell>], base: &[usize], base_cells: &[Cell], chosen: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    // Only the units holding the first uncovered candidate can complete the cover, which keeps the branching small
+    let Some(uncovered) = base_cells.iter().find(|cell| chosen.iter().all(|i| !unit_cells[*i].contains(cell))) else {
+        let mut cover = chosen.clone();
+        cover.sort_unstable();
+        if chosen.len() == size && !found.contains(&cover) {
+            found.push(cover);
+        }
+        return;
+    };
+    if chosen.len() == size {
+        return;
+    }
+
+    for i in 0..unit_cells.len() {
+        if !base.contains(&i) && unit_cells[i].contains(uncovered) && !chosen.contains(&i) {
+            chosen.push(i);
+            cover_units(size, unit_cells, base, base_cells, chosen, found);
+            chosen.pop();
+        }
+    }
+}
+
+fn can_cover(size: usize, unit_cells: &[Vec<Cell>], base: &[usize], base_cells: &[Cell], chosen: &mut Vec<usize>) -> bool {
+    let Some(uncovered) = base_cells.iter().find(|cell| chosen.iter().all(|i| !unit_cells[*i].contains(cell))) else {
+        return true;
+    };
+    if chosen.len() == size {
+        return false;
+    }
+
+    (0..unit_cells.len()).any(|i| {
+        if base.contains(&i) || !unit_cells[i].contains(uncovered) {
+            return false;
+        }
+        chosen.push(i);
+        let covered = can_cover(size, unit_cells, base, base_cells, chosen);
+        chosen.pop();
+        covered
+    })
+}
+
+/// Returns whether a fish is mutant, or `None` for a basic row and column fish.
+fn classify(base: &[Unit], cover: &[Unit]) -> Option<bool> {
+    let all = |units: &[Unit], allowed: &dyn Fn(&Unit) -> bool| units.iter().all(allowed);
+    let is_row = |unit: &Unit| matches!(unit, Unit::Row(_));
+    let is_col = |unit: &Unit| matches!(unit, Unit::Col(_));
+    let not_col = |unit: &Unit| !is_col(unit);
+    let not_row = |unit: &Unit| !is_row(unit);
+
+    if (all(base, &is_row) && all(cover, &is_col)) || (all(base, &is_col) && all(cover, &is_row)) {
+        None
+    } else {
+        Some(!((all(base, &not_col) && all(cover, &not_row)) || (all(base, &not_row) && all(cover, &not_col))))
+    }
+}
+
+impl ComplexFish {
+    pub fn deduction(&self) -> Deduction {
+        let mut deduction = Deduction::new(self.base.clone(), 1 << self.val);
+        deduction.eliminated.extend(self.targets.iter().map(|cell| (*cell, self.val)));
+        deduction
+    }
 }
 
-pub fn from_board4(board: &Board) -> Rc<[XWing<4>]> {
-    let unit_groups: Vec<_> = (0..(SIZE - 3))
-        .flat_map(|a| ((a + 1)..(SIZE - 2)).flat_map(move |b| ((b + 1)..(SIZE - 1)).flat_map(move |c| ((c + 1)..SIZE).map(move |d| [a, b, c, d]))))
-        .collect();
-
-    unit_groups[..]
-        .iter()
-        .flat_map(|a| unit_groups[..].iter().map(move |b| (a, b)))
-        .flat_map(|(rows, cols)| {
-            [true, false].iter().map(|clear_rows| XWing {
-                clear_rows: *clear_rows,
-                rows: *rows,
-                cols: *cols,
-                val: 0,
-            })
-        })
-        .flat_map(|xwing| {
-            (1..=SIZE).map(move |d| {
-                #[allow(clippy::cast_possible_truncation)]
-                XWing {
-                    clear_rows: xwing.clear_rows,
-                    rows: xwing.rows,
-                    cols: xwing.cols,
-                    val: d as u16,
+impl<const S: usize> XWing<S> {
+    fn new(clear_rows: bool, base: [usize; S], cover: [usize; S], val: u32, fins: Vec<Cell>, sashimi: bool) -> Self {
+        let (rows, cols) = if clear_rows { (cover, base) } else { (base, cover) };
+        XWing {
+            clear_rows,
+            rows,
+            cols,
+            val,
+            fins,
+            sashimi,
+        }
+    }
+
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let size = board.size;
+        let mut cells: Vec<_> = self
+            .rows
+            .iter()
+            .flat_map(|row| self.cols.iter().map(|col| Cell { row: *row, col: *col }))
+            .filter(|cell| is_set!(board[*cell], self.val))
+            .collect();
+        cells.extend(&self.fins);
+
+        let mut deduction = Deduction::new(cells, 1 << self.val);
+        if self.sashimi {
+            deduction.rule = Some("sashimi");
+        }
+        let targets: Vec<_> = if self.clear_rows {
+            self.rows.iter().flat_map(|row| (0..size).filter(|col| !self.cols.contains(col)).map(|col| Cell { row: *row, col })).collect()
+        } else {
+            self.cols.iter().flat_map(|col| (0..size).filter(|row| !self.rows.contains(row)).map(|row| Cell { row, col: *col })).collect()
+        };
+        deduction.eliminated.extend(
+            targets
+                .into_iter()
+                .filter(|cell| is_set!(board[*cell], self.val))
+                .filter(|cell| self.fins.iter().all(|fin| fin.can_see(board, cell)))
+                .map(|cell| (cell, self.val)),
+        );
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Removes 1 from every cell of `row` outside `keep`.
+    fn restrict_row(board: &mut Board, row: usize, keep: &[usize]) {
+        for col in (0..9).filter(|col| !keep.contains(col)) {
+            board[Cell { row, col }] &= !(1 << 1);
+        }
+    }
+
+    #[test]
+    fn finned_x_wing_eliminates_what_sees_the_fin() {
+        // 1 lies in c1 and c5 of the first and fifth rows, with a fin in r5c6
+        let mut board = Board::new(9);
+        restrict_row(&mut board, 0, &[0, 4]);
+        restrict_row(&mut board, 4, &[0, 4, 5]);
+
+        let deductions = FinnedXWings::<2>.find(&board);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].rule, None);
+        assert_eq!(deductions[0].eliminated, [(Cell { row: 3, col: 4 }, 1), (Cell { row: 5, col: 4 }, 1)]);
+    }
+
+    #[test]
+    fn sashimi_x_wing_misses_a_corner() {
+        // 1 lies in c1 and c5 of the first row but only c1 and c6 of the fifth, so either r1c5 or r5c6 serves as the fin
+        let mut board = Board::new(9);
+        restrict_row(&mut board, 0, &[0, 4]);
+        restrict_row(&mut board, 4, &[0, 5]);
+
+        let deductions = FinnedXWings::<2>.find(&board);
+        assert!(deductions.iter().all(|deduction| deduction.rule == Some("sashimi")));
+        let mut eliminated: Vec<_> = deductions.iter().flat_map(|deduction| deduction.eliminated.clone()).collect();
+        eliminated.sort_unstable();
+        let expected: Vec<_> = [(1, 5), (2, 5), (3, 4), (5, 4)].map(|(row, col)| (Cell { row, col }, 1)).into();
+        assert_eq!(eliminated, expected);
+    }
+
+    #[test]
+    fn franken_fish_covers_a_box_with_columns() {
+        // 1 lies in c1 and c5 of the first row and only in c5 of the middle box, so columns 1 and 5 hold both
+        let mut board = Board::new(9);
+        restrict_row(&mut board, 0, &[0, 4]);
+        for row in 3..6 {
+            for col in 3..6 {
+                if col != 4 || row == 4 {
+                    board[Cell { row, col }] &= !(1 << 1);
                 }
-            })
-        })
-        .filter(|xwing| is_valid!(board, xwing))
-        .collect()
+            }
+        }
+
+        let fish = from_board_complex::<2>(&board, false);
+        // The rest of both columns, outside the middle box
+        let mut expected: Vec<_> = (1..9).map(|row| Cell { row, col: 0 }).chain([1, 2, 6, 7, 8].map(|row| Cell { row, col: 4 })).collect();
+        expected.sort_unstable();
+        assert!(fish.iter().any(|fish| fish.targets == expected));
+        assert!(MutantFish::<2>.find(&board).is_empty());
+    }
+
+    #[test]
+    fn mutant_fish_mixes_a_row_and_column_base() {
+        // 1 lies in c1 and c2 of the first row and in r4 and r5 of the last column, so the two boxes hold both
+        let mut board = Board::new(9);
+        restrict_row(&mut board, 0, &[0, 1]);
+        for row in (0..9).filter(|row| *row != 3 && *row != 4) {
+            board[Cell { row, col: 8 }] &= !(1 << 1);
+        }
+
+        let fish = from_board_complex::<2>(&board, true);
+        assert_eq!(fish.len(), 1);
+        let top_left = (1..3).flat_map(|row| (0..3).map(move |col| Cell { row, col }));
+        let middle_right = (3..6).flat_map(|row| (6..8).map(move |col| Cell { row, col }));
+        let expected: Vec<_> = top_left.chain(middle_right).collect();
+        assert_eq!(fish[0].targets, expected);
+    }
 }
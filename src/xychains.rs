@@ -0,0 +1,121 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{Board, Cell},
+    misc::{bivalue_cells, cells, digits, is_set},
+    solver::Technique,
+    step::Deduction,
+};
+
+pub struct XYChains {
+    /// Longest chain searched for, in cells.
+    pub max_length: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct XYChain {
+    pub cells: Vec<Cell>,
+    /// The digit held by both ends, one of which must be true.
+    pub val: u32,
+}
+
+impl Default for XYChains {
+    fn default() -> Self {
+        XYChains { max_length: 8 }
+    }
+}
+
+impl Technique for XYChains {
+    fn name(&self) -> &'static str {
+        "XY-Chain"
+    }
+
+    fn difficulty(&self) -> f32 {
+        6.6
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        let mut seen = vec![];
+        from_board(board, self.max_length)
+            .iter()
+            .map(|chain| chain.deduction(board))
+            .filter(|deduction| {
+                // Longer chains reaching eliminations already made by a shorter one add nothing
+                let is_new = deduction.eliminated.iter().any(|elimination| !seen.contains(elimination));
+                seen.extend(deduction.eliminated.iter().copied());
+                is_new
+            })
+            .collect()
+    }
+}
+
+/// Finds chains of bivalue cells, each seeing the next and sharing the digit the previous cell did not use, whose ends
+/// both hold `val`. Chains are returned shortest first and have at least three cells, as two are a naked pair.
+pub fn from_board(board: &Board, max_length: usize) -> Rc<[XYChain]> {
+    let useful_cells = bivalue_cells(board);
+    let mut found = vec![];
+
+    for start in &useful_cells {
+        for val in digits(board[*start]) {
+            extend_chain(board, &useful_cells, &mut vec![*start], val, board[*start] & !(1 << val), max_length, &mut found);
+        }
+    }
+
+    found.sort_by_key(|chain| chain.cells.len());
+    found.into()
+}
+
+fn extend_chain(board: &Board, useful_cells: &[Cell], chain: &mut Vec<Cell>, val: u32, link: u32, max_length: usize, found: &mut Vec<XYChain>) {
+    let end = chain[chain.len() - 1];
+    if chain.len() >= 3 && link == 1 << val && chain[0] < end {
+        found.push(XYChain { cells: chain.clone(), val });
+    }
+    if chain.len() == max_length {
+        return;
+    }
+
+    for cell in useful_cells {
+        if board[*cell] & link != 0 && !chain.contains(cell) && end.can_see(board, cell) {
+            chain.push(*cell);
+            extend_chain(board, useful_cells, chain, val, board[*cell] & !link, max_length, found);
+            chain.pop();
+        }
+    }
+}
+
+impl XYChain {
+    pub fn deduction(&self, board: &Board) -> Deduction {
+        let (start, end) = (self.cells[0], self.cells[self.cells.len() - 1]);
+        let mut deduction = Deduction::new(self.cells.clone(), 1 << self.val);
+        deduction.eliminated.extend(
+            cells(board)
+                .iter()
+                .filter(|cell| !self.cells.contains(cell) && is_set!(board[**cell], self.val))
+                .filter(|cell| cell.can_see(board, &start) && cell.can_see(board, &end))
+                .map(|cell| (*cell, self.val)),
+        );
+        deduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xy_chain_eliminates_the_end_digit_from_cells_seeing_both_ends() {
+        // r1c1 {1,2} - r1c5 {2,3} - r5c5 {3,4} - r5c2 {4,1}, so one end is 1
+        let mut board = Board::new(9);
+        board[Cell { row: 0, col: 0 }] = 1 << 1 | 1 << 2;
+        board[Cell { row: 0, col: 4 }] = 1 << 2 | 1 << 3;
+        board[Cell { row: 4, col: 4 }] = 1 << 3 | 1 << 4;
+        board[Cell { row: 4, col: 1 }] = 1 << 4 | 1 << 1;
+
+        let deductions = XYChains::default().find(&board);
+        assert_eq!(deductions.len(), 1);
+        let mut eliminated = deductions[0].eliminated.clone();
+        eliminated.sort_unstable();
+        let expected: Vec<_> = [(0, 1), (1, 1), (2, 1), (3, 0), (4, 0), (5, 0)].map(|(row, col)| (Cell { row, col }, 1)).into();
+        assert_eq!(eliminated, expected);
+    }
+}
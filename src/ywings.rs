@@ -2,38 +2,47 @@ use std::rc::Rc;
 
 use crate::{
     board::{Board, Cell},
-    misc::is_set,
-    SIZE,
+    misc::{bivalue_cells, is_set},
+    solver::Technique,
+    step::Deduction,
 };
 
+pub struct YWings;
+
 #[derive(Debug)]
 pub struct YWing {
     pub origin: Cell,
     pub foci: (Cell, Cell),
     pub target: Cell,
-    pub val: u16,
+    pub val: u32,
 }
 
-pub fn from_board(board: &Board) -> Rc<[YWing]> {
-    let cells: Vec<_> = (0..SIZE).flat_map(|row| (0..SIZE).map(move |col| Cell { row, col })).collect();
+impl Technique for YWings {
+    fn name(&self) -> &'static str {
+        "Y-Wing"
+    }
 
-    let useful_cells: Vec<_> = (0..SIZE)
-        .flat_map(|row| {
-            (0..SIZE).map(move |col| {
-                if let Some(vals) = board.get_cell_coords(row, col) {
-                    if vals.count_ones() == 2 {
-                        Some(Cell { row, col })
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+    fn difficulty(&self) -> f32 {
+        4.2
+    }
+
+    fn find(&self, board: &Board) -> Vec<Deduction> {
+        from_board(board)
+            .iter()
+            .map(|ywing| {
+                let mut deduction = Deduction::new(vec![ywing.origin, ywing.foci.0, ywing.foci.1], board[ywing.origin] | board[ywing.foci.0] | board[ywing.foci.1]);
+                deduction.eliminated.push((ywing.target, ywing.val));
+                deduction
             })
-        })
-        .map(|group| group.unwrap_or(Cell { row: SIZE, col: SIZE }))
-        .filter(|cell| cell.row != SIZE)
-        .collect();
+            .collect()
+    }
+}
+
+pub fn from_board(board: &Board) -> Rc<[YWing]> {
+    let size = board.size;
+    let cells: Vec<_> = (0..size).flat_map(|row| (0..size).map(move |col| Cell { row, col })).collect();
+
+    let useful_cells = bivalue_cells(board);
 
     useful_cells[..]
         .iter()
@@ -47,12 +56,11 @@ pub fn from_board(board: &Board) -> Rc<[YWing]> {
                 .filter_map(|(a, b)| {
                     let foci_vals = (board[*a], board[*b]);
                     if board[*origin] ^ foci_vals.0 ^ foci_vals.1 == 0 {
-                        #[allow(clippy::cast_possible_truncation)]
                         Some(YWing {
                             origin: *origin,
                             foci: (*a, *b),
                             target: Cell { row: 0, col: 0 },
-                            val: (foci_vals.0 & foci_vals.1).trailing_zeros() as u16,
+                            val: (foci_vals.0 & foci_vals.1).trailing_zeros(),
                         })
                     } else {
                         None